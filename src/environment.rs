@@ -1,31 +1,133 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::{RefCell, RefMut},
+    collections::HashMap,
+    fmt::Debug,
+    io::Write,
+    rc::Rc,
+};
 
 use crate::{
     errors::LispComputerError,
-    parse::Expression,
+    parse::{Expression, Span},
     process::{
-        AdditionProcessor, AndProcessor, CondProcessor, DefineProcessor, DivisionProcessor,
-        DoProcessor, EqualProcessor, Function, GreaterEqualProcessor, GreaterThanProcessor,
-        IfProcessor, LambdaProcessor, LessEqualProcessor, LessThanProcessor, LetProcessor,
-        MultiplicationProcessor, OrProcessor, SubtractionProcessor,
+        AbsProcessor, AdditionProcessor, AndProcessor, AppendProcessor, ApplyProcessor,
+        AtanProcessor, BeginProcessor, BoxProcessor, CallWithValuesProcessor, CarProcessor,
+        CaseLambdaProcessor, CaseProcessor, CdrProcessor, CeilingProcessor, CondProcessor,
+        ConsProcessor, CosProcessor, DefineProcessor, DefineSyntaxProcessor, DefmacroProcessor,
+        DisplayProcessor, DivisionProcessor, DoProcessor, EqProcessor, EqualProcessor,
+        ErrorObjectIrritantsProcessor, ErrorObjectMessageProcessor, ErrorObjectProcessor,
+        ErrorProcessor, ExactIntegerSqrtProcessor, ExactProcessor, ExpProcessor, ExptProcessor,
+        FilterProcessor, FloorProcessor, FoldLeftProcessor, FoldRightProcessor, ForEachProcessor,
+        Function, GcdProcessor, GreaterEqualProcessor, GreaterThanProcessor, GuardProcessor,
+        Handler, IfProcessor, InexactProcessor, IsBoxProcessor, IsExactIntegerProcessor,
+        IsExactProcessor, IsInexactProcessor, IsIntegerProcessor, IsListProcessor,
+        IsNumberProcessor, IsRationalProcessor, IsRealProcessor, LambdaProcessor, LcmProcessor,
+        LengthProcessor, LessEqualProcessor, LessThanProcessor, LetProcessor, LetStarProcessor,
+        LetSyntaxProcessor, LetValuesProcessor, LetrecProcessor, LetrecStarProcessor,
+        ListProcessor, ListRefProcessor, ListTailProcessor, LogProcessor, MacroexpandOnceProcessor,
+        MacroexpandProcessor, MapProcessor, MaxProcessor, MinProcessor, ModuloProcessor,
+        MultiplicationProcessor, NewlineProcessor, NullProcessor, NumberToStringProcessor,
+        OrProcessor, PairProcessor, Procedure, QuasiquoteProcessor, QuoteProcessor,
+        QuotientProcessor, RaiseContinuableProcessor, RaiseProcessor, ReduceProcessor,
+        RemainderProcessor, ReverseProcessor, RoundProcessor, SetBoxProcessor, SetProcessor,
+        SinProcessor, SqrtProcessor, StringToNumberProcessor, StringToSymbolProcessor,
+        SubtractionProcessor, SymbolProcessor, SymbolToStringProcessor, SyntaxRulesProcessor, Tail,
+        TanProcessor, TruncateProcessor, UnboxProcessor, UnlessProcessor, ValuesProcessor,
+        WhenProcessor, WithExceptionHandlerProcessor, eval_args, tail_call_procedure,
     },
-    value::Value,
+    value::{Symbol, Value},
 };
 
 pub trait Environment {
+    /// Evaluates `(symbol args...)`, `span` being where the symbol was read.
     fn process_variable(
         &self,
         symbol: &str,
+        span: &Span,
         args: &[Expression],
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError>;
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError>;
     fn set_variable(&self, name: String, value: Value);
-    fn get_variable(&self, name: &str, variables: &HashMap<&str, Value>) -> Option<Value>;
+    fn get_variable(&self, name: &str, scope: &Scope) -> Option<Value>;
+    /// Assigns the nearest binding of `name` visible from `scope`, as done by
+    /// `set!`. Returns false when the name is unbound.
+    fn assign_variable(&self, name: &str, value: Value, scope: &Scope) -> bool;
+    fn get_builtin(&self, name: &str) -> Option<&dyn Procedure<Self>>
+    where
+        Self: Sized;
+    /// Installs an exception handler, innermost last.
+    fn push_handler(&self, handler: Handler);
+    fn pop_handler(&self) -> Option<Handler>;
+    /// Where `display` and `newline` write.
+    fn output(&self) -> RefMut<'_, dyn Write>;
+}
+
+/// A frame of local bindings. Lookups walk outward through the parent frames, so
+/// inner bindings shadow outer ones.
+#[derive(Default)]
+pub struct Scope {
+    variables: RefCell<HashMap<String, Value>>,
+    parent: Option<Rc<Scope>>,
+}
+
+impl Debug for Scope {
+    // frames may be reachable from the lambdas they hold, so only names are printed
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scope")
+            .field("variables", &self.variables.borrow().keys())
+            .field("parent", &self.parent)
+            .finish()
+    }
+}
+
+impl Scope {
+    pub fn new(parent: &Rc<Scope>) -> Rc<Self> {
+        Rc::new(Scope {
+            variables: RefCell::default(),
+            parent: Some(parent.clone()),
+        })
+    }
+    pub fn insert(&self, name: String, value: Value) {
+        self.variables.borrow_mut().insert(name, value);
+    }
+    pub fn get(&self, name: &str) -> Option<Value> {
+        if let Some(value) = self.variables.borrow().get(name) {
+            return Some(value.clone());
+        }
+        self.parent.as_ref().and_then(|parent| parent.get(name))
+    }
+    /// Replaces the nearest binding of `name`, giving the value back when no
+    /// frame binds it.
+    pub fn update(&self, name: &str, value: Value) -> Option<Value> {
+        if let Some(binding) = self.variables.borrow_mut().get_mut(name) {
+            *binding = value;
+            return None;
+        }
+        match &self.parent {
+            Some(parent) => parent.update(name, value),
+            None => Some(value),
+        }
+    }
+    /// The outermost frame, where definitions are global.
+    pub fn is_top_level(&self) -> bool {
+        self.parent.is_none()
+    }
 }
 
-#[derive(Debug)]
 pub struct GlobalEnvironment {
     variables: RefCell<HashMap<String, Value>>,
+    functions: HashMap<String, Box<dyn Function<Self>>>,
+    builtins: HashMap<String, Box<dyn Procedure<Self>>>,
+    handlers: RefCell<Vec<Handler>>,
+    output: RefCell<Box<dyn Write>>,
+}
+
+impl Debug for GlobalEnvironment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GlobalEnvironment")
+            .field("variables", &self.variables)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Default for GlobalEnvironment {
@@ -33,8 +135,13 @@ impl Default for GlobalEnvironment {
         let mut variables = HashMap::new();
         variables.insert("#f".to_string(), Value::Boolean(false));
         variables.insert("#t".to_string(), Value::Boolean(true));
+        variables.insert("nil".to_string(), Value::Nil);
         Self {
             variables: RefCell::new(variables),
+            functions: Self::language_function_map(),
+            builtins: Self::builtin_map(),
+            handlers: RefCell::default(),
+            output: RefCell::new(Box::new(std::io::stdout())),
         }
     }
 }
@@ -43,83 +150,105 @@ impl Environment for GlobalEnvironment {
     fn process_variable(
         &self,
         symbol: &str,
+        span: &Span,
         args: &[Expression],
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         if let Some(func) = self.get_language_function(symbol) {
-            return func.process(args, self, variables);
+            return func.process(args, self, scope);
         };
-        if let Some(Value::Lambda(lamda)) = self.get_variable(symbol, variables) {
-            return lamda.process(args, self, variables);
+        match self.get_variable(symbol, scope) {
+            Some(procedure @ (Value::Lambda(_) | Value::Builtin(_))) => {
+                let args = eval_args(args, self, scope)?;
+                tail_call_procedure(&procedure, args, self)
+            }
+            Some(Value::Macro(transformer)) => {
+                let expansion = transformer.expand(symbol, args, span, self)?;
+                Ok(Tail::Eval(expansion, scope.clone()))
+            }
+            Some(Value::Unassigned) => {
+                Err(LispComputerError::UnassignedVariable(symbol.to_string()))
+            }
+            Some(other) => Err(LispComputerError::NotCallable(other)),
+            None => Err(LispComputerError::UnboundFunction(symbol.to_string())),
         }
-        Err(LispComputerError::UnboundFunction(symbol.to_string()))
     }
     fn set_variable(&self, name: String, value: Value) {
         self.variables.borrow_mut().insert(name, value);
     }
-    fn get_variable(&self, name: &str, variables: &HashMap<&str, Value>) -> Option<Value> {
-        if let Some(value) = self.variables.borrow().get(name).cloned() {
-            Some(value)
-        } else {
-            variables.get(name).cloned()
+    fn get_variable(&self, name: &str, scope: &Scope) -> Option<Value> {
+        scope
+            .get(name)
+            .or_else(|| self.variables.borrow().get(name).cloned())
+            .or_else(|| {
+                self.builtins
+                    .contains_key(name)
+                    .then(|| Value::Builtin(Symbol::new(name)))
+            })
+    }
+    fn assign_variable(&self, name: &str, value: Value, scope: &Scope) -> bool {
+        let Some(value) = scope.update(name, value) else {
+            return true;
+        };
+        if !self.variables.borrow().contains_key(name) && !self.builtins.contains_key(name) {
+            return false;
         }
+        self.set_variable(name.to_string(), value);
+        true
+    }
+    fn get_builtin(&self, name: &str) -> Option<&dyn Procedure<Self>> {
+        self.builtins.get(name).map(Box::as_ref)
+    }
+    fn push_handler(&self, handler: Handler) {
+        self.handlers.borrow_mut().push(handler);
+    }
+    fn pop_handler(&self) -> Option<Handler> {
+        self.handlers.borrow_mut().pop()
+    }
+    fn output(&self) -> RefMut<'_, dyn Write> {
+        RefMut::map(self.output.borrow_mut(), Box::as_mut)
     }
 }
 
 impl GlobalEnvironment {
-    fn get_language_function<T: Environment>(&self, name: &str) -> Option<Box<dyn Function<T>>> {
-        let mut functions = Self::language_function_map();
-        functions.remove(name)
+    /// An environment whose `display` and `newline` write to `output` instead of
+    /// stdout.
+    #[cfg(test)]
+    pub fn with_output(output: impl Write + 'static) -> Self {
+        Self {
+            output: RefCell::new(Box::new(output)),
+            ..Self::default()
+        }
+    }
+    fn get_language_function(&self, name: &str) -> Option<&dyn Function<Self>> {
+        self.functions.get(name).map(Box::as_ref)
     }
+    /// Special forms, which receive their arguments unevaluated.
     fn language_function_map<T: Environment>() -> HashMap<String, Box<dyn Function<T>>> {
         let mut functions: HashMap<String, Box<dyn Function<T>>> = HashMap::new();
-        functions.insert(
-            <AdditionProcessor as Function<T>>::name(&AdditionProcessor).to_string(),
-            Box::new(AdditionProcessor),
-        );
-        functions.insert(
-            <SubtractionProcessor as Function<T>>::name(&SubtractionProcessor).to_string(),
-            Box::new(SubtractionProcessor),
-        );
-        functions.insert(
-            <MultiplicationProcessor as Function<T>>::name(&MultiplicationProcessor).to_string(),
-            Box::new(MultiplicationProcessor),
-        );
-        functions.insert(
-            <DivisionProcessor as Function<T>>::name(&DivisionProcessor).to_string(),
-            Box::new(DivisionProcessor),
-        );
         functions.insert(
             <DefineProcessor as Function<T>>::name(&DefineProcessor).to_string(),
             Box::new(DefineProcessor),
         );
+        functions.insert(
+            <SetProcessor as Function<T>>::name(&SetProcessor).to_string(),
+            Box::new(SetProcessor),
+        );
         functions.insert(
             <LambdaProcessor as Function<T>>::name(&LambdaProcessor).to_string(),
             Box::new(LambdaProcessor),
         );
         functions.insert(
-            <EqualProcessor as Function<T>>::name(&EqualProcessor).to_string(),
-            Box::new(EqualProcessor),
+            <CaseLambdaProcessor as Function<T>>::name(&CaseLambdaProcessor).to_string(),
+            Box::new(CaseLambdaProcessor),
         );
         functions.insert(
             <IfProcessor as Function<T>>::name(&IfProcessor).to_string(),
             Box::new(IfProcessor),
         );
         functions.insert(
-            <GreaterThanProcessor as Function<T>>::name(&GreaterThanProcessor).to_string(),
-            Box::new(GreaterThanProcessor),
-        );
-        functions.insert(
-            <LessThanProcessor as Function<T>>::name(&LessThanProcessor).to_string(),
-            Box::new(LessThanProcessor),
-        );
-        functions.insert(
-            <LessEqualProcessor as Function<T>>::name(&LessEqualProcessor).to_string(),
-            Box::new(LessEqualProcessor),
-        );
-        functions.insert(
-            <GreaterEqualProcessor as Function<T>>::name(&GreaterEqualProcessor).to_string(),
-            Box::new(GreaterEqualProcessor),
+            <BeginProcessor as Function<T>>::name(&BeginProcessor).to_string(),
+            Box::new(BeginProcessor),
         );
         functions.insert(
             <OrProcessor as Function<T>>::name(&OrProcessor).to_string(),
@@ -133,14 +262,404 @@ impl GlobalEnvironment {
             <CondProcessor as Function<T>>::name(&CondProcessor).to_string(),
             Box::new(CondProcessor),
         );
+        functions.insert(
+            <WhenProcessor as Function<T>>::name(&WhenProcessor).to_string(),
+            Box::new(WhenProcessor),
+        );
+        functions.insert(
+            <UnlessProcessor as Function<T>>::name(&UnlessProcessor).to_string(),
+            Box::new(UnlessProcessor),
+        );
+        functions.insert(
+            <CaseProcessor as Function<T>>::name(&CaseProcessor).to_string(),
+            Box::new(CaseProcessor),
+        );
         functions.insert(
             <LetProcessor as Function<T>>::name(&LetProcessor).to_string(),
             Box::new(LetProcessor),
         );
+        functions.insert(
+            <LetStarProcessor as Function<T>>::name(&LetStarProcessor).to_string(),
+            Box::new(LetStarProcessor),
+        );
+        functions.insert(
+            <LetrecProcessor as Function<T>>::name(&LetrecProcessor).to_string(),
+            Box::new(LetrecProcessor),
+        );
+        functions.insert(
+            <LetrecStarProcessor as Function<T>>::name(&LetrecStarProcessor).to_string(),
+            Box::new(LetrecStarProcessor),
+        );
+        functions.insert(
+            <LetValuesProcessor as Function<T>>::name(&LetValuesProcessor).to_string(),
+            Box::new(LetValuesProcessor),
+        );
         functions.insert(
             <DoProcessor as Function<T>>::name(&DoProcessor).to_string(),
             Box::new(DoProcessor),
         );
+        functions.insert(
+            <QuoteProcessor as Function<T>>::name(&QuoteProcessor).to_string(),
+            Box::new(QuoteProcessor),
+        );
+        functions.insert(
+            <QuasiquoteProcessor as Function<T>>::name(&QuasiquoteProcessor).to_string(),
+            Box::new(QuasiquoteProcessor),
+        );
+        functions.insert(
+            <GuardProcessor as Function<T>>::name(&GuardProcessor).to_string(),
+            Box::new(GuardProcessor),
+        );
+        functions.insert(
+            <SyntaxRulesProcessor as Function<T>>::name(&SyntaxRulesProcessor).to_string(),
+            Box::new(SyntaxRulesProcessor),
+        );
+        functions.insert(
+            <DefineSyntaxProcessor as Function<T>>::name(&DefineSyntaxProcessor).to_string(),
+            Box::new(DefineSyntaxProcessor),
+        );
+        functions.insert(
+            <LetSyntaxProcessor as Function<T>>::name(&LetSyntaxProcessor).to_string(),
+            Box::new(LetSyntaxProcessor),
+        );
+        functions.insert(
+            <DefmacroProcessor as Function<T>>::name(&DefmacroProcessor).to_string(),
+            Box::new(DefmacroProcessor),
+        );
         functions
     }
+    /// Built-in procedures, which can also be used as values.
+    fn builtin_map<T: Environment>() -> HashMap<String, Box<dyn Procedure<T>>> {
+        let mut builtins: HashMap<String, Box<dyn Procedure<T>>> = HashMap::new();
+        builtins.insert(
+            <AdditionProcessor as Procedure<T>>::name(&AdditionProcessor).to_string(),
+            Box::new(AdditionProcessor),
+        );
+        builtins.insert(
+            <SubtractionProcessor as Procedure<T>>::name(&SubtractionProcessor).to_string(),
+            Box::new(SubtractionProcessor),
+        );
+        builtins.insert(
+            <MultiplicationProcessor as Procedure<T>>::name(&MultiplicationProcessor).to_string(),
+            Box::new(MultiplicationProcessor),
+        );
+        builtins.insert(
+            <DivisionProcessor as Procedure<T>>::name(&DivisionProcessor).to_string(),
+            Box::new(DivisionProcessor),
+        );
+        builtins.insert(
+            <EqualProcessor as Procedure<T>>::name(&EqualProcessor).to_string(),
+            Box::new(EqualProcessor),
+        );
+        builtins.insert(
+            <GreaterThanProcessor as Procedure<T>>::name(&GreaterThanProcessor).to_string(),
+            Box::new(GreaterThanProcessor),
+        );
+        builtins.insert(
+            <LessThanProcessor as Procedure<T>>::name(&LessThanProcessor).to_string(),
+            Box::new(LessThanProcessor),
+        );
+        builtins.insert(
+            <LessEqualProcessor as Procedure<T>>::name(&LessEqualProcessor).to_string(),
+            Box::new(LessEqualProcessor),
+        );
+        builtins.insert(
+            <GreaterEqualProcessor as Procedure<T>>::name(&GreaterEqualProcessor).to_string(),
+            Box::new(GreaterEqualProcessor),
+        );
+        builtins.insert(
+            <ConsProcessor as Procedure<T>>::name(&ConsProcessor).to_string(),
+            Box::new(ConsProcessor),
+        );
+        builtins.insert(
+            <CarProcessor as Procedure<T>>::name(&CarProcessor).to_string(),
+            Box::new(CarProcessor),
+        );
+        builtins.insert(
+            <CdrProcessor as Procedure<T>>::name(&CdrProcessor).to_string(),
+            Box::new(CdrProcessor),
+        );
+        builtins.insert(
+            <ListProcessor as Procedure<T>>::name(&ListProcessor).to_string(),
+            Box::new(ListProcessor),
+        );
+        builtins.insert(
+            <NullProcessor as Procedure<T>>::name(&NullProcessor).to_string(),
+            Box::new(NullProcessor),
+        );
+        builtins.insert(
+            <PairProcessor as Procedure<T>>::name(&PairProcessor).to_string(),
+            Box::new(PairProcessor),
+        );
+        builtins.insert(
+            <IsListProcessor as Procedure<T>>::name(&IsListProcessor).to_string(),
+            Box::new(IsListProcessor),
+        );
+        builtins.insert(
+            <LengthProcessor as Procedure<T>>::name(&LengthProcessor).to_string(),
+            Box::new(LengthProcessor),
+        );
+        builtins.insert(
+            <AppendProcessor as Procedure<T>>::name(&AppendProcessor).to_string(),
+            Box::new(AppendProcessor),
+        );
+        builtins.insert(
+            <ReverseProcessor as Procedure<T>>::name(&ReverseProcessor).to_string(),
+            Box::new(ReverseProcessor),
+        );
+        builtins.insert(
+            <ListTailProcessor as Procedure<T>>::name(&ListTailProcessor).to_string(),
+            Box::new(ListTailProcessor),
+        );
+        builtins.insert(
+            <ListRefProcessor as Procedure<T>>::name(&ListRefProcessor).to_string(),
+            Box::new(ListRefProcessor),
+        );
+        builtins.insert(
+            <SymbolProcessor as Procedure<T>>::name(&SymbolProcessor).to_string(),
+            Box::new(SymbolProcessor),
+        );
+        builtins.insert(
+            <SymbolToStringProcessor as Procedure<T>>::name(&SymbolToStringProcessor).to_string(),
+            Box::new(SymbolToStringProcessor),
+        );
+        builtins.insert(
+            <StringToSymbolProcessor as Procedure<T>>::name(&StringToSymbolProcessor).to_string(),
+            Box::new(StringToSymbolProcessor),
+        );
+        builtins.insert(
+            <EqProcessor as Procedure<T>>::name(&EqProcessor).to_string(),
+            Box::new(EqProcessor),
+        );
+        builtins.insert(
+            <MapProcessor as Procedure<T>>::name(&MapProcessor).to_string(),
+            Box::new(MapProcessor),
+        );
+        builtins.insert(
+            <ForEachProcessor as Procedure<T>>::name(&ForEachProcessor).to_string(),
+            Box::new(ForEachProcessor),
+        );
+        builtins.insert(
+            <FilterProcessor as Procedure<T>>::name(&FilterProcessor).to_string(),
+            Box::new(FilterProcessor),
+        );
+        builtins.insert(
+            <ReduceProcessor as Procedure<T>>::name(&ReduceProcessor).to_string(),
+            Box::new(ReduceProcessor),
+        );
+        builtins.insert(
+            <FoldLeftProcessor as Procedure<T>>::name(&FoldLeftProcessor).to_string(),
+            Box::new(FoldLeftProcessor),
+        );
+        builtins.insert(
+            <FoldRightProcessor as Procedure<T>>::name(&FoldRightProcessor).to_string(),
+            Box::new(FoldRightProcessor),
+        );
+        builtins.insert(
+            <ApplyProcessor as Procedure<T>>::name(&ApplyProcessor).to_string(),
+            Box::new(ApplyProcessor),
+        );
+        builtins.insert(
+            <DisplayProcessor as Procedure<T>>::name(&DisplayProcessor).to_string(),
+            Box::new(DisplayProcessor),
+        );
+        builtins.insert(
+            <NewlineProcessor as Procedure<T>>::name(&NewlineProcessor).to_string(),
+            Box::new(NewlineProcessor),
+        );
+        builtins.insert(
+            <RaiseProcessor as Procedure<T>>::name(&RaiseProcessor).to_string(),
+            Box::new(RaiseProcessor),
+        );
+        builtins.insert(
+            <RaiseContinuableProcessor as Procedure<T>>::name(&RaiseContinuableProcessor)
+                .to_string(),
+            Box::new(RaiseContinuableProcessor),
+        );
+        builtins.insert(
+            <ErrorProcessor as Procedure<T>>::name(&ErrorProcessor).to_string(),
+            Box::new(ErrorProcessor),
+        );
+        builtins.insert(
+            <ErrorObjectProcessor as Procedure<T>>::name(&ErrorObjectProcessor).to_string(),
+            Box::new(ErrorObjectProcessor),
+        );
+        builtins.insert(
+            <ErrorObjectMessageProcessor as Procedure<T>>::name(&ErrorObjectMessageProcessor)
+                .to_string(),
+            Box::new(ErrorObjectMessageProcessor),
+        );
+        builtins.insert(
+            <ErrorObjectIrritantsProcessor as Procedure<T>>::name(&ErrorObjectIrritantsProcessor)
+                .to_string(),
+            Box::new(ErrorObjectIrritantsProcessor),
+        );
+        builtins.insert(
+            <WithExceptionHandlerProcessor as Procedure<T>>::name(&WithExceptionHandlerProcessor)
+                .to_string(),
+            Box::new(WithExceptionHandlerProcessor),
+        );
+        builtins.insert(
+            <MacroexpandOnceProcessor as Procedure<T>>::name(&MacroexpandOnceProcessor).to_string(),
+            Box::new(MacroexpandOnceProcessor),
+        );
+        builtins.insert(
+            <MacroexpandProcessor as Procedure<T>>::name(&MacroexpandProcessor).to_string(),
+            Box::new(MacroexpandProcessor),
+        );
+        builtins.insert(
+            <BoxProcessor as Procedure<T>>::name(&BoxProcessor).to_string(),
+            Box::new(BoxProcessor),
+        );
+        builtins.insert(
+            <IsBoxProcessor as Procedure<T>>::name(&IsBoxProcessor).to_string(),
+            Box::new(IsBoxProcessor),
+        );
+        builtins.insert(
+            <UnboxProcessor as Procedure<T>>::name(&UnboxProcessor).to_string(),
+            Box::new(UnboxProcessor),
+        );
+        builtins.insert(
+            <SetBoxProcessor as Procedure<T>>::name(&SetBoxProcessor).to_string(),
+            Box::new(SetBoxProcessor),
+        );
+        builtins.insert(
+            <ValuesProcessor as Procedure<T>>::name(&ValuesProcessor).to_string(),
+            Box::new(ValuesProcessor),
+        );
+        builtins.insert(
+            <CallWithValuesProcessor as Procedure<T>>::name(&CallWithValuesProcessor).to_string(),
+            Box::new(CallWithValuesProcessor),
+        );
+        builtins.insert(
+            <IsNumberProcessor as Procedure<T>>::name(&IsNumberProcessor).to_string(),
+            Box::new(IsNumberProcessor),
+        );
+        builtins.insert(
+            <IsRealProcessor as Procedure<T>>::name(&IsRealProcessor).to_string(),
+            Box::new(IsRealProcessor),
+        );
+        builtins.insert(
+            <IsRationalProcessor as Procedure<T>>::name(&IsRationalProcessor).to_string(),
+            Box::new(IsRationalProcessor),
+        );
+        builtins.insert(
+            <IsIntegerProcessor as Procedure<T>>::name(&IsIntegerProcessor).to_string(),
+            Box::new(IsIntegerProcessor),
+        );
+        builtins.insert(
+            <IsExactIntegerProcessor as Procedure<T>>::name(&IsExactIntegerProcessor).to_string(),
+            Box::new(IsExactIntegerProcessor),
+        );
+        builtins.insert(
+            <IsExactProcessor as Procedure<T>>::name(&IsExactProcessor).to_string(),
+            Box::new(IsExactProcessor),
+        );
+        builtins.insert(
+            <IsInexactProcessor as Procedure<T>>::name(&IsInexactProcessor).to_string(),
+            Box::new(IsInexactProcessor),
+        );
+        builtins.insert(
+            <ExactProcessor as Procedure<T>>::name(&ExactProcessor).to_string(),
+            Box::new(ExactProcessor),
+        );
+        builtins.insert(
+            <InexactProcessor as Procedure<T>>::name(&InexactProcessor).to_string(),
+            Box::new(InexactProcessor),
+        );
+        builtins.insert(
+            <QuotientProcessor as Procedure<T>>::name(&QuotientProcessor).to_string(),
+            Box::new(QuotientProcessor),
+        );
+        builtins.insert(
+            <RemainderProcessor as Procedure<T>>::name(&RemainderProcessor).to_string(),
+            Box::new(RemainderProcessor),
+        );
+        builtins.insert(
+            <ModuloProcessor as Procedure<T>>::name(&ModuloProcessor).to_string(),
+            Box::new(ModuloProcessor),
+        );
+        builtins.insert(
+            <AbsProcessor as Procedure<T>>::name(&AbsProcessor).to_string(),
+            Box::new(AbsProcessor),
+        );
+        builtins.insert(
+            <MinProcessor as Procedure<T>>::name(&MinProcessor).to_string(),
+            Box::new(MinProcessor),
+        );
+        builtins.insert(
+            <MaxProcessor as Procedure<T>>::name(&MaxProcessor).to_string(),
+            Box::new(MaxProcessor),
+        );
+        builtins.insert(
+            <GcdProcessor as Procedure<T>>::name(&GcdProcessor).to_string(),
+            Box::new(GcdProcessor),
+        );
+        builtins.insert(
+            <LcmProcessor as Procedure<T>>::name(&LcmProcessor).to_string(),
+            Box::new(LcmProcessor),
+        );
+        builtins.insert(
+            <ExptProcessor as Procedure<T>>::name(&ExptProcessor).to_string(),
+            Box::new(ExptProcessor),
+        );
+        builtins.insert(
+            <ExpProcessor as Procedure<T>>::name(&ExpProcessor).to_string(),
+            Box::new(ExpProcessor),
+        );
+        builtins.insert(
+            <LogProcessor as Procedure<T>>::name(&LogProcessor).to_string(),
+            Box::new(LogProcessor),
+        );
+        builtins.insert(
+            <SqrtProcessor as Procedure<T>>::name(&SqrtProcessor).to_string(),
+            Box::new(SqrtProcessor),
+        );
+        builtins.insert(
+            <ExactIntegerSqrtProcessor as Procedure<T>>::name(&ExactIntegerSqrtProcessor)
+                .to_string(),
+            Box::new(ExactIntegerSqrtProcessor),
+        );
+        builtins.insert(
+            <SinProcessor as Procedure<T>>::name(&SinProcessor).to_string(),
+            Box::new(SinProcessor),
+        );
+        builtins.insert(
+            <CosProcessor as Procedure<T>>::name(&CosProcessor).to_string(),
+            Box::new(CosProcessor),
+        );
+        builtins.insert(
+            <TanProcessor as Procedure<T>>::name(&TanProcessor).to_string(),
+            Box::new(TanProcessor),
+        );
+        builtins.insert(
+            <AtanProcessor as Procedure<T>>::name(&AtanProcessor).to_string(),
+            Box::new(AtanProcessor),
+        );
+        builtins.insert(
+            <FloorProcessor as Procedure<T>>::name(&FloorProcessor).to_string(),
+            Box::new(FloorProcessor),
+        );
+        builtins.insert(
+            <CeilingProcessor as Procedure<T>>::name(&CeilingProcessor).to_string(),
+            Box::new(CeilingProcessor),
+        );
+        builtins.insert(
+            <RoundProcessor as Procedure<T>>::name(&RoundProcessor).to_string(),
+            Box::new(RoundProcessor),
+        );
+        builtins.insert(
+            <TruncateProcessor as Procedure<T>>::name(&TruncateProcessor).to_string(),
+            Box::new(TruncateProcessor),
+        );
+        builtins.insert(
+            <NumberToStringProcessor as Procedure<T>>::name(&NumberToStringProcessor).to_string(),
+            Box::new(NumberToStringProcessor),
+        );
+        builtins.insert(
+            <StringToNumberProcessor as Procedure<T>>::name(&StringToNumberProcessor).to_string(),
+            Box::new(StringToNumberProcessor),
+        );
+        builtins
+    }
 }
@@ -1,14 +1,21 @@
+use std::{fmt::Display, rc::Rc};
+
 use rustyline::error::ReadlineError;
 
-use crate::{parse::Expression, value::Value};
+use crate::{
+    parse::{Expression, Span},
+    value::{ErrorObject, Value},
+};
 
 #[derive(thiserror::Error, Debug)]
 pub enum LispError {
-    #[error("Invalid input")]
-    InvalidInput,
+    #[error("{}: invalid input, expected {}",.span,.expected)]
+    InvalidInput { span: Span, expected: String },
     #[error("readline error")]
     ReadlineError(#[from] ReadlineError),
-    #[error("computer error")]
+    #[error("{}",.0)]
+    IoError(#[from] std::io::Error),
+    #[error("{}",.0)]
     ComputerError(#[from] LispComputerError),
 }
 
@@ -26,10 +33,118 @@ pub enum LispComputerError {
     TypeMismatch1 { operation: String, left: Value },
     #[error("Invalid arguments for function {}: {}",.0,.1.iter().map(|e| format!("{}", e)).collect::<Vec<String>>().join(" "))]
     InvalidArguments(String, Vec<Expression>),
+    #[error("Not callable: {}",.0)]
+    NotCallable(Value),
     #[error("Variable not found: {}",.0)]
     NotFoundVariable(String),
+    #[error("Variable used before its assignment: {}",.0)]
+    UnassignedVariable(String),
     #[error("Arity mismatch {}: expected {}, got {}",.0,.1,.2)]
     ArityMismatch(String, usize, usize),
+    #[error("Division by zero in {}",.0)]
+    DivisionByZero(String),
+    #[error("Exact result of {} is too large",.0)]
+    ExactResultTooLarge(String),
+    #[error("Index {} out of range for {}",.1,.0)]
+    IndexOutOfRange(String, usize),
+    #[error("else must be the last {} clause",.0)]
+    MisplacedElse(String),
     #[error("Let naming not return")]
     LetNamingNotReturn,
+    #[error("{}",.0)]
+    IoError(#[from] std::io::Error),
+    #[error("{}: {}",.0,.1)]
+    Located(Span, Box<LispComputerError>),
+    /// A value thrown by `raise` or `error` that no handler caught.
+    #[error("{}",raised_message(.0))]
+    Raised(Value),
+    /// The error with the lambda calls it passed through, innermost first.
+    #[error("{}",.0)]
+    Backtrace(Box<LispComputerError>, Vec<Frame>),
+}
+
+fn raised_message(value: &Value) -> String {
+    match value {
+        Value::Error(error) => error.to_string(),
+        value => format!("Uncaught exception: {value}"),
+    }
+}
+
+/// A call to a lambda that was active when an error was raised.
+#[derive(Debug)]
+pub struct Frame {
+    pub name: Option<Rc<str>>,
+    /// where the lambda was called, unknown for calls made by builtins
+    pub span: Span,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "in {}", self.name.as_deref().unwrap_or("<lambda>"))?;
+        if self.span.is_known() {
+            write!(f, " called at {}", self.span)?;
+        }
+        Ok(())
+    }
+}
+
+impl LispComputerError {
+    /// Records that the error was raised by the form at `span`, unless an inner
+    /// form already claimed it.
+    pub fn at(self, span: &Span) -> Self {
+        match self {
+            LispComputerError::Located(_, _) => self,
+            _ if !span.is_known() => self,
+            LispComputerError::Backtrace(error, frames) => {
+                LispComputerError::Backtrace(Box::new(error.at(span)), frames)
+            }
+            _ => LispComputerError::Located(span.clone(), Box::new(self)),
+        }
+    }
+    /// Adds a lambda call the error unwound through to its backtrace.
+    pub fn in_frame(self, frame: Frame) -> Self {
+        match self {
+            LispComputerError::Backtrace(error, mut frames) => {
+                frames.push(frame);
+                LispComputerError::Backtrace(error, frames)
+            }
+            _ => LispComputerError::Backtrace(Box::new(self), vec![frame]),
+        }
+    }
+    /// The value a `guard` or exception handler receives for this error: the raised
+    /// value itself, or an error object describing a built-in error.
+    pub fn condition(&self) -> Value {
+        let irritants = match self {
+            LispComputerError::Located(_, error) | LispComputerError::Backtrace(error, _) => {
+                return error.condition();
+            }
+            LispComputerError::Raised(value) => return value.clone(),
+            LispComputerError::TypeMismatch1 { left, .. } => vec![left.clone()],
+            LispComputerError::TypeMismatch2 { left, right, .. } => {
+                vec![left.clone(), right.clone()]
+            }
+            LispComputerError::NotCallable(value) => vec![value.clone()],
+            _ => Vec::new(),
+        };
+        Value::Error(Rc::new(ErrorObject {
+            message: self.to_string(),
+            irritants,
+        }))
+    }
+    /// The value thrown by `raise` or `error`, `None` for built-in errors.
+    pub fn raised(&self) -> Option<&Value> {
+        match self {
+            LispComputerError::Located(_, error) | LispComputerError::Backtrace(error, _) => {
+                error.raised()
+            }
+            LispComputerError::Raised(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn backtrace(&self) -> &[Frame] {
+        match self {
+            LispComputerError::Backtrace(_, frames) => frames,
+            _ => &[],
+        }
+    }
 }
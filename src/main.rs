@@ -1,42 +1,130 @@
-use std::collections::HashMap;
+use std::{
+    io::{Read, Write},
+    process::ExitCode,
+    rc::Rc,
+};
 
+use environment::{Environment, GlobalEnvironment, Scope};
 use errors::LispError;
 
 mod environment;
 mod errors;
 mod parse;
 mod process;
+mod validator;
 mod value;
 
-use parse::parse_expression;
-use rustyline::{DefaultEditor, error::ReadlineError};
+use parse::{is_incomplete, parse_error, parse_program, read_program};
+use process::macroexpand;
+use rustyline::{Editor, error::ReadlineError, history::DefaultHistory};
+use validator::InputValidator;
+use value::Value;
 
-fn main() -> Result<(), LispError> {
-    let mut rl = DefaultEditor::new()?;
-    let env = environment::GlobalEnvironment::default();
+const USAGE: &str = "usage: lisp [script.lisp | -e expression | -] [args...]";
 
-    loop {
-        let readline = rl.readline(">> ");
-        match readline {
-            Ok(line) => {
-                rl.add_history_entry(line.as_str())?;
+fn main() -> Result<ExitCode, LispError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (file, source, program_args) = match args.as_slice() {
+        [] => {
+            repl()?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        [flag, expression, rest @ ..] if flag == "-e" => ("-e", expression.to_string(), rest),
+        [flag, rest @ ..] if flag == "-" => {
+            let mut source = String::new();
+            std::io::stdin().read_to_string(&mut source)?;
+            ("<stdin>", source, rest)
+        }
+        [path, rest @ ..] if !path.starts_with('-') => match std::fs::read_to_string(path) {
+            Ok(source) => (path.as_str(), source, rest),
+            Err(err) => {
+                eprintln!("Error: {path}: {err}");
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            return Ok(ExitCode::from(2));
+        }
+    };
 
-                let (_, expression) =
-                    parse_expression(&line).map_err(|_| LispError::InvalidInput)?;
+    let env = GlobalEnvironment::default();
+    let scope = Rc::new(Scope::default());
+    env.set_variable(
+        "*args*".to_string(),
+        Value::list(
+            program_args
+                .iter()
+                .map(|arg| Value::String(arg.to_string())),
+        ),
+    );
+    match run_program(&source, &Rc::from(file), &env, &scope) {
+        Ok(Value::Nil) => Ok(ExitCode::SUCCESS),
+        Ok(value) => {
+            // only an expression given with `-e` has its value printed
+            if args[0] == "-e" {
+                println!("{value}");
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(err) => {
+            eprintln!("Error: {err}");
+            if let LispError::ComputerError(err) = &err {
+                for frame in err.backtrace() {
+                    eprintln!("  {frame}");
+                }
+            }
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
 
-                println!("{expression}");
+/// Evaluates every top-level form of a program, returning the value of the last one.
+fn run_program(
+    source: &str,
+    file: &Rc<str>,
+    env: &GlobalEnvironment,
+    scope: &Rc<Scope>,
+) -> Result<Value, LispError> {
+    let expressions = read_program(source, file)?;
+    let mut result = Value::Nil;
+    for expression in expressions {
+        result = expression.eval_values(env, scope)?;
+    }
+    Ok(result)
+}
 
-                let result = expression.eval(&env, &HashMap::new());
+fn repl() -> Result<(), LispError> {
+    let mut rl = Editor::<InputValidator, DefaultHistory>::new()?;
+    rl.set_helper(Some(InputValidator));
+    let env = GlobalEnvironment::default();
+    let scope = Rc::new(Scope::default());
+    // lines of a form that is not complete yet, when the input is not a terminal the
+    // validator is bypassed and lines are collected here instead
+    let mut pending = String::new();
 
-                match result {
-                    Ok(data) => println!("Result: {}", data),
-                    Err(err) => println!("Error:{err}"),
+    loop {
+        let prompt = if pending.is_empty() { ">> " } else { ".. " };
+        let readline = rl.readline(prompt);
+        match readline {
+            Ok(line) => {
+                pending.push_str(&line);
+                pending.push('\n');
+                if is_incomplete(&pending) {
+                    continue;
                 }
+                let input = std::mem::take(&mut pending);
+                rl.add_history_entry(input.trim_end())?;
+
+                run_line(&input, &env, &scope, &mut std::io::stdout())?;
 
-                if line.trim() == "exit" {
+                if input.trim() == "exit" {
                     break;
                 }
             }
+            Err(ReadlineError::Interrupted) if !pending.is_empty() => {
+                pending.clear();
+            }
             Err(ReadlineError::Interrupted) => {
                 println!("CTRL-C");
                 break;
@@ -53,3 +141,85 @@ fn main() -> Result<(), LispError> {
     }
     Ok(())
 }
+
+/// Evaluates every form on the line, writing each form and its result to `out`.
+/// Errors are reported without ending the session.
+fn run_line(
+    line: &str,
+    env: &GlobalEnvironment,
+    scope: &Rc<Scope>,
+    out: &mut impl Write,
+) -> std::io::Result<()> {
+    let (rest, expressions) = parse_program(line).unwrap_or((line, Vec::new()));
+
+    for expression in expressions {
+        writeln!(out, "{expression}")?;
+
+        let result = match macroexpand(&expression, env, scope) {
+            Ok(expansion) => {
+                if expansion != expression {
+                    writeln!(out, "Expansion: {expansion}")?;
+                }
+                expansion.eval_values(env, scope)
+            }
+            Err(err) => Err(err),
+        };
+
+        match result {
+            Ok(data) => writeln!(out, "Result: {}", data)?,
+            Err(err) => {
+                writeln!(out, "Error:{err}")?;
+                for frame in err.backtrace() {
+                    writeln!(out, "  {frame}")?;
+                }
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        writeln!(out, "Error:{}", parse_error(line, rest, None))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::{
+        environment::{GlobalEnvironment, Scope},
+        run_line,
+    };
+
+    fn run_lines(env: &GlobalEnvironment, lines: &[&str]) -> anyhow::Result<String> {
+        let scope = Rc::new(Scope::default());
+        let mut out = Vec::new();
+        for line in lines {
+            run_line(line, env, &scope, &mut out)?;
+        }
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn run_line_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let output = run_lines(
+            &env,
+            &[
+                "(define x 2) (car 1) (+ x",
+                "(* x 3)",
+                "(undefined-function)",
+            ],
+        )?;
+        // the failing form and the unfinished one are reported, the definition is kept
+        assert_eq!(
+            output,
+            "(define x 2)\nResult: nil\n\
+             (car 1)\nError:1:14: Operation car mismatch: get 1\n\
+             Error:1:22: invalid input, expected `)` to close the list\n\
+             (* x 3)\nResult: 6\n\
+             (undefined-function)\nError:1:1: Unbound function:undefined-function\n"
+        );
+        Ok(())
+    }
+}
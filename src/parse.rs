@@ -1,39 +1,103 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{fmt::Display, rc::Rc};
 
 use nom::{
-    IResult, Parser,
+    IResult, Input as _, Parser,
     branch::alt,
     bytes::complete::tag,
-    character::complete::{multispace0, multispace1, none_of, one_of},
-    combinator::{map, not, peek, recognize},
-    multi::{many1, separated_list0},
-    number::complete::double,
+    character::complete::{char, multispace1, none_of, not_line_ending, one_of},
+    combinator::{map, not, peek, recognize, value},
+    error::{Error, ErrorKind},
+    multi::{many0, many1, separated_list0},
     sequence::delimited,
 };
+use nom_locate::LocatedSpan;
 use string::parse_string;
 
 use crate::{
-    environment::Environment, errors::LispComputerError, process::process_expression_list,
-    value::Value,
+    environment::{Environment, Scope},
+    errors::{Frame, LispComputerError, LispError},
+    process::{Tail, process_expression_list},
+    value::{Number, Symbol, Value},
 };
 
 mod string;
 
-#[derive(Debug, PartialEq, Clone)]
+/// Parser input, carrying the name of the file being read.
+type Input<'a> = LocatedSpan<&'a str, Option<&'a Rc<str>>>;
+
+/// The position an expression starts at. Expressions built by the interpreter
+/// rather than read from source have the default, unknown span.
+#[derive(Debug, Clone, Default)]
+pub struct Span {
+    pub file: Option<Rc<str>>,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Span {
+    fn of(input: &Input) -> Self {
+        Span {
+            file: input.extra.cloned(),
+            line: input.location_line(),
+            column: input.get_utf8_column() as u32,
+        }
+    }
+    /// Locates the byte `offset` of `source`.
+    fn at_offset(source: &str, offset: usize, file: Option<&Rc<str>>) -> Self {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Span {
+            file: file.cloned(),
+            line: before.matches('\n').count() as u32 + 1,
+            column: before[line_start..].chars().count() as u32 + 1,
+        }
+    }
+    pub fn is_known(&self) -> bool {
+        self.line > 0
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum Expression {
-    Number(f64),
-    Variable(String),
-    List(Vec<Expression>),
-    String(String),
-    NamingList(String, Vec<Expression>),
+    Number(Number, Span),
+    Variable(String, Span),
+    List(Vec<Expression>, Span),
+    String(String, Span),
+    NamingList(String, Vec<Expression>, Span),
+}
+
+/// Expressions are compared by structure, where they were read does not matter.
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Expression::Number(left, _), Expression::Number(right, _)) => left == right,
+            (Expression::Variable(left, _), Expression::Variable(right, _)) => left == right,
+            (Expression::List(left, _), Expression::List(right, _)) => left == right,
+            (Expression::String(left, _), Expression::String(right, _)) => left == right,
+            (
+                Expression::NamingList(left_name, left, _),
+                Expression::NamingList(right_name, right, _),
+            ) => left_name == right_name && left == right,
+            _ => false,
+        }
+    }
 }
 
 impl Display for Expression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Expression::Number(number) => write!(f, "{}", number),
-            Expression::Variable(name) => write!(f, "{}", name),
-            Expression::List(expressions) => write!(
+            Expression::Number(number, _) => write!(f, "{}", number),
+            Expression::Variable(name, _) => write!(f, "{}", name),
+            Expression::List(expressions, _) => write!(
                 f,
                 "({})",
                 expressions
@@ -42,8 +106,8 @@ impl Display for Expression {
                     .collect::<Vec<String>>()
                     .join(" ")
             ),
-            Expression::String(string) => write!(f, "\"{}\"", string),
-            Expression::NamingList(name, expressions) => write!(
+            Expression::String(string, _) => write!(f, "\"{}\"", string),
+            Expression::NamingList(name, expressions, _) => write!(
                 f,
                 "{name}({})",
                 expressions
@@ -57,29 +121,285 @@ impl Display for Expression {
 }
 
 impl Expression {
+    pub fn span(&self) -> &Span {
+        match self {
+            Expression::Number(_, span)
+            | Expression::Variable(_, span)
+            | Expression::List(_, span)
+            | Expression::String(_, span)
+            | Expression::NamingList(_, _, span) => span,
+        }
+    }
+    /// Evaluates the expression where a single value is expected.
     pub fn eval<T: Environment>(
         &self,
         env: &T,
-        variables: &HashMap<&str, Value>,
+        scope: &Rc<Scope>,
+    ) -> Result<Value, LispComputerError> {
+        self.eval_values(env, scope)?
+            .single()
+            .map_err(|err| err.at(self.span()))
+    }
+    /// Evaluates the expression, which may return the several results of
+    /// `values`. Only the forms receiving those results unpack them.
+    pub fn eval_values<T: Environment>(
+        &self,
+        env: &T,
+        scope: &Rc<Scope>,
     ) -> Result<Value, LispComputerError> {
+        // the lambda whose body is being evaluated, tail calls replace it
+        let mut frame = None;
+        let mut call_site = self.span().clone();
+        let mut tail = self.eval_step(env, scope);
+        loop {
+            match tail {
+                Ok(Tail::Value(value)) => return Ok(value),
+                Ok(Tail::Eval(expression, scope)) => {
+                    call_site = expression.span().clone();
+                    tail = expression.eval_step(env, &scope);
+                }
+                Ok(Tail::Call(name, body, scope)) => {
+                    let Some((last, leading)) = body.split_last() else {
+                        tail = Ok(Tail::Value(Value::Nil));
+                        continue;
+                    };
+                    frame = Some(Frame {
+                        name,
+                        span: std::mem::replace(&mut call_site, last.span().clone()),
+                    });
+                    tail = leading
+                        .iter()
+                        .try_for_each(|expression| expression.eval_values(env, &scope).map(drop))
+                        .and_then(|()| last.eval_step(env, &scope));
+                }
+                Err(err) => {
+                    return Err(match frame {
+                        Some(frame) => err.in_frame(frame),
+                        None => err,
+                    });
+                }
+            }
+        }
+    }
+    /// Evaluates the expression up to its next tail position. Errors are located
+    /// at the innermost form that raised them.
+    fn eval_step<T: Environment>(
+        &self,
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         match self {
-            Expression::Number(data) => Ok(Value::Number(*data)),
-            Expression::Variable(value) => env
-                .get_variable(value, variables)
-                .ok_or(LispComputerError::NotFoundVariable(value.to_string())),
-            Expression::List(expressions) => process_expression_list(expressions, env, variables),
-            Expression::String(string) => Ok(Value::String(string.to_string())),
-            Expression::NamingList(_, _) => Err(LispComputerError::LetNamingNotReturn),
+            Expression::Number(data, _) => Ok(Tail::Value(Value::Number(data.clone()))),
+            Expression::Variable(value, span) => match env.get_variable(value, scope) {
+                Some(Value::Unassigned) => {
+                    Err(LispComputerError::UnassignedVariable(value.to_string()).at(span))
+                }
+                Some(value) => Ok(Tail::Value(value)),
+                None => Err(LispComputerError::NotFoundVariable(value.to_string()).at(span)),
+            },
+            Expression::List(expressions, span) => {
+                process_expression_list(expressions, env, scope).map_err(|err| err.at(span))
+            }
+            Expression::String(string, _) => Ok(Tail::Value(Value::String(string.to_string()))),
+            Expression::NamingList(_, _, span) => {
+                Err(LispComputerError::LetNamingNotReturn.at(span))
+            }
+        }
+    }
+    /// Converts the expression into the value it denotes as quoted data.
+    pub fn to_datum(&self) -> Result<Value, LispComputerError> {
+        match self {
+            Expression::Number(data, _) => Ok(Value::Number(data.clone())),
+            Expression::Variable(name, _) => Ok(match name.as_str() {
+                "#t" => Value::Boolean(true),
+                "#f" => Value::Boolean(false),
+                _ => Value::Symbol(Symbol::new(name)),
+            }),
+            Expression::String(string, _) => Ok(Value::String(string.to_string())),
+            Expression::List(expressions, _) => {
+                let (items, tail) = split_dotted_list(expressions)?;
+                let tail = match tail {
+                    Some(tail) => tail.to_datum()?,
+                    None => Value::Nil,
+                };
+                items
+                    .iter()
+                    .rev()
+                    .try_fold(tail, |tail, item| Ok(Value::cons(item.to_datum()?, tail)))
+            }
+            Expression::NamingList(_, _, _) => Err(LispComputerError::InvalidArguments(
+                "quote".to_string(),
+                vec![self.clone()],
+            )),
+        }
+    }
+}
+
+impl Expression {
+    /// Converts quoted data back into code, the inverse of [`Expression::to_datum`].
+    /// The new expressions are all located at `span`.
+    pub fn from_datum(value: &Value, span: &Span) -> Result<Expression, LispComputerError> {
+        match value {
+            Value::Number(number) => Ok(Expression::Number(number.clone(), span.clone())),
+            Value::String(string) => Ok(Expression::String(string.to_string(), span.clone())),
+            Value::Symbol(symbol) => Ok(Expression::Variable(symbol.to_string(), span.clone())),
+            Value::Boolean(true) => Ok(Expression::Variable("#t".to_string(), span.clone())),
+            Value::Boolean(false) => Ok(Expression::Variable("#f".to_string(), span.clone())),
+            Value::Nil => Ok(Expression::List(Vec::new(), span.clone())),
+            Value::Pair(_) => {
+                let mut expressions = Vec::new();
+                let mut tail = value;
+                while let Value::Pair(pair) = tail {
+                    expressions.push(Expression::from_datum(&pair.car, span)?);
+                    tail = &pair.cdr;
+                }
+                if *tail != Value::Nil {
+                    expressions.push(Expression::Variable(".".to_string(), span.clone()));
+                    expressions.push(Expression::from_datum(tail, span)?);
+                }
+                Ok(Expression::List(expressions, span.clone()))
+            }
+            value => Err(LispComputerError::TypeMismatch1 {
+                operation: "quote".to_string(),
+                left: value.clone(),
+            }),
         }
     }
 }
 
+/// Splits `(a b . c)` into its items and the expression after the dot, if any.
+pub fn split_dotted_list(
+    expressions: &[Expression],
+) -> Result<(&[Expression], Option<&Expression>), LispComputerError> {
+    let is_dot = |expression: &Expression| matches!(expression, Expression::Variable(name, _) if name == ".");
+    match expressions {
+        [items @ .., dot, tail] if is_dot(dot) && !items.is_empty() => {
+            if items.iter().any(is_dot) || is_dot(tail) {
+                return Err(LispComputerError::InvalidArguments(
+                    ".".to_string(),
+                    expressions.to_vec(),
+                ));
+            }
+            Ok((items, Some(tail)))
+        }
+        _ if expressions.iter().any(is_dot) => Err(LispComputerError::InvalidArguments(
+            ".".to_string(),
+            expressions.to_vec(),
+        )),
+        _ => Ok((expressions, None)),
+    }
+}
+
+/// Parses a single expression without a file name.
+#[cfg(test)]
 pub fn parse_expression(input: &str) -> IResult<&str, Expression> {
+    unlocated(parse_located_expression(Input::new_extra(input, None)))
+}
+
+/// Parses every top-level form of the input, returning the input it could not parse.
+pub fn parse_program(input: &str) -> IResult<&str, Vec<Expression>> {
+    parse_program_in(input, None)
+}
+
+/// Parses a whole program read from `file`. Unlike [`parse_program`], input that is
+/// not a complete expression is an error.
+pub fn read_program(source: &str, file: &Rc<str>) -> Result<Vec<Expression>, LispError> {
+    let (rest, expressions) = parse_program_in(source, Some(file)).unwrap_or((source, Vec::new()));
+    if rest.is_empty() {
+        Ok(expressions)
+    } else {
+        Err(parse_error(source, rest, Some(file)))
+    }
+}
+
+fn parse_program_in<'a>(
+    input: &'a str,
+    file: Option<&'a Rc<str>>,
+) -> IResult<&'a str, Vec<Expression>> {
+    unlocated(parse_expression_inner(Input::new_extra(input, file)))
+}
+
+/// Drops the locations from the input of a parse result.
+fn unlocated<'a, O>(result: IResult<Input<'a>, O>) -> IResult<&'a str, O> {
+    result
+        .map(|(rest, output)| (*rest.fragment(), output))
+        .map_err(|err| err.map_input(|input| *input.fragment()))
+}
+
+/// Describes why the parser stopped at `rest`, the unparsed end of `source`.
+pub fn parse_error(source: &str, rest: &str, file: Option<&Rc<str>>) -> LispError {
+    let start = source.len() - rest.len();
+    let (offset, expected) = match diagnose(rest) {
+        Some((offset, expected)) => (start + offset, expected),
+        None => (start, "an expression"),
+    };
+    LispError::InvalidInput {
+        span: Span::at_offset(source, offset, file),
+        expected: expected.to_string(),
+    }
+}
+
+/// Finds the first unbalanced delimiter of the input, returning its offset and
+/// what was expected there.
+fn diagnose(input: &str) -> Option<(usize, &'static str)> {
+    let mut lists = Vec::new();
+    let mut comments = Vec::new();
+    let mut string = None;
+    let mut chars = input.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        match (string.is_some(), !comments.is_empty(), c) {
+            (_, true, '|') if next_is('#') => {
+                comments.pop();
+            }
+            (_, true, '#') if next_is('|') => comments.push(offset),
+            (_, true, _) => {}
+            (true, _, '\\') => {
+                chars.next();
+            }
+            (true, _, '"') => string = None,
+            (true, _, _) => {}
+            (false, _, '"') => string = Some(offset),
+            (false, _, ';') => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            (false, _, '#') if next_is('|') => comments.push(offset),
+            (false, _, '(') => lists.push(offset),
+            (false, _, ')') if lists.pop().is_none() => {
+                return Some((offset, "an expression instead of `)`"));
+            }
+            (false, _, '\'' | '`' | ',') => {
+                let rest = input[offset + c.len_utf8()..].trim_start_matches('@');
+                if rest.trim_start().is_empty() || rest.trim_start().starts_with(')') {
+                    return Some((offset, "an expression after the quote"));
+                }
+            }
+            (false, _, _) => {}
+        }
+    }
+    if let Some(offset) = string {
+        Some((offset, "`\"` to close the string"))
+    } else if let Some(offset) = comments.pop() {
+        Some((offset, "`|#` to close the comment"))
+    } else {
+        lists.pop().map(|offset| (offset, "`)` to close the list"))
+    }
+}
+
+fn parse_located_expression(input: Input) -> IResult<Input, Expression> {
+    let span = Span::of(&input);
     let (input, data) = alt((
-        map(double, Expression::Number),
+        parse_quoted,
+        map(parse_number, |number| {
+            Expression::Number(number, span.clone())
+        }),
         map(
             (tag("("), parse_expression_inner, tag(")")),
-            |(_, data, _)| Expression::List(data),
+            |(_, data, _)| Expression::List(data, span.clone()),
         ),
         map(
             (
@@ -88,46 +408,193 @@ pub fn parse_expression(input: &str) -> IResult<&str, Expression> {
                 parse_expression_inner,
                 tag(")"),
             ),
-            |(name, _, expr, _)| Expression::NamingList(name, expr),
+            |(name, _, expr, _)| Expression::NamingList(name, expr, span.clone()),
         ),
-        map(parse_lisp_variable, Expression::Variable),
-        map(parse_string, Expression::String),
+        map(parse_lisp_variable, |name| {
+            Expression::Variable(name, span.clone())
+        }),
+        map(parse_located_string, |string| {
+            Expression::String(string, span.clone())
+        }),
     ))
     .parse(input)?;
     Ok((input, data))
 }
 
-fn parse_expression_inner(input: &str) -> IResult<&str, Vec<Expression>> {
+/// Returns true when the input ends inside a list, a string or a block comment, so
+/// more lines are needed to complete the form.
+pub fn is_incomplete(input: &str) -> bool {
+    let mut depth = 0usize;
+    let mut comment_depth = 0usize;
+    let mut in_string = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_string, comment_depth > 0, c) {
+            (_, true, '|') if chars.next_if_eq(&'#').is_some() => comment_depth -= 1,
+            (_, true, '#') if chars.next_if_eq(&'|').is_some() => comment_depth += 1,
+            (_, true, _) => {}
+            (true, _, '\\') => {
+                chars.next();
+            }
+            (true, _, '"') => in_string = false,
+            (true, _, _) => {}
+            (false, _, '"') => in_string = true,
+            (false, _, ';') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            (false, _, '#') if chars.next_if_eq(&'|').is_some() => comment_depth += 1,
+            // a datum comment is followed by an ordinary expression
+            (false, _, '#') if chars.next_if_eq(&';').is_some() => {}
+            (false, _, '(') => depth += 1,
+            // a stray closing parenthesis can never be balanced, the input is read so
+            // the reader reports it
+            (false, _, ')') if depth == 0 => return false,
+            (false, _, ')') => depth -= 1,
+            (false, _, _) => {}
+        }
+    }
+    in_string || depth > 0 || comment_depth > 0
+}
+
+/// Expands the reader shorthands `'x`, `` `x ``, `,x` and `,@x` into their long forms.
+fn parse_quoted(input: Input) -> IResult<Input, Expression> {
+    let span = Span::of(&input);
+    let (input, (prefix, expression)) = (
+        alt((tag("'"), tag("`"), tag(",@"), tag(","))),
+        parse_located_expression,
+    )
+        .parse(input)?;
+    let name = match *prefix.fragment() {
+        "'" => "quote",
+        "`" => "quasiquote",
+        ",@" => "unquote-splicing",
+        _ => "unquote",
+    };
+    Ok((
+        input,
+        Expression::List(
+            vec![
+                Expression::Variable(name.to_string(), span.clone()),
+                expression,
+            ],
+            span,
+        ),
+    ))
+}
+
+fn parse_expression_inner(input: Input) -> IResult<Input, Vec<Expression>> {
     let (input, data) = delimited(
-        multispace0,
-        separated_list0(multispace1, parse_expression),
-        multispace0,
+        parse_whitespace0,
+        separated_list0(parse_whitespace1, parse_located_expression),
+        parse_whitespace0,
     )
     .parse(input)?;
     Ok((input, data))
 }
 
-fn parse_lisp_variable(input: &str) -> IResult<&str, String> {
-    let valid_char = none_of(" \t\n\r()\"");
-    let (input, data) =
-        recognize((not(peek(one_of("0123456789"))), many1(valid_char))).parse(input)?;
+/// Whitespace and comments, both of which separate expressions.
+fn parse_whitespace0(input: Input) -> IResult<Input, ()> {
+    value((), many0(alt((value((), multispace1), parse_comment)))).parse(input)
+}
+
+fn parse_whitespace1(input: Input) -> IResult<Input, ()> {
+    value((), many1(alt((value((), multispace1), parse_comment)))).parse(input)
+}
+
+/// `; line`, `#| block |#` and `#;datum` comments.
+fn parse_comment(input: Input) -> IResult<Input, ()> {
+    alt((
+        value((), (char(';'), not_line_ending)),
+        parse_block_comment,
+        value((), (tag("#;"), parse_whitespace0, parse_located_expression)),
+    ))
+    .parse(input)
+}
+
+/// Block comments nest, so `#| a #| b |# c |#` is a single comment.
+fn parse_block_comment(input: Input) -> IResult<Input, ()> {
+    let (input, _) = tag("#|").parse(input)?;
+    let mut rest = *input.fragment();
+    let mut depth = 1;
+    while depth > 0 {
+        if let Some(tail) = rest.strip_prefix("|#") {
+            depth -= 1;
+            rest = tail;
+        } else if let Some(tail) = rest.strip_prefix("#|") {
+            depth += 1;
+            rest = tail;
+        } else {
+            let mut chars = rest.chars();
+            if chars.next().is_none() {
+                return Err(nom::Err::Error(Error::new(input, ErrorKind::TakeUntil)));
+            }
+            rest = chars.as_str();
+        }
+    }
+    Ok((input.take_from(input.len() - rest.len()), ()))
+}
+
+/// Characters that may appear in a symbol or a number.
+fn atom_char(input: Input) -> IResult<Input, char> {
+    none_of(" \t\n\r()\"'`,;").parse(input)
+}
+
+/// A token of atom characters that reads as a number. Other tokens, such as `-`,
+/// `...` or `info`, are left to be read as symbols.
+fn parse_number(input: Input) -> IResult<Input, Number> {
+    let (rest, token) = recognize(many1(atom_char)).parse(input)?;
+    match Number::parse(token.fragment(), 10) {
+        Some(number) => Ok((rest, number)),
+        None => Err(nom::Err::Error(Error::new(input, ErrorKind::Float))),
+    }
+}
+
+fn parse_lisp_variable(input: Input) -> IResult<Input, String> {
+    // comment openers are never the start of a symbol, even an unterminated one
+    let (input, data) = recognize((
+        not(peek(one_of("0123456789"))),
+        not(peek(alt((tag("#|"), tag("#;"))))),
+        many1(atom_char),
+    ))
+    .parse(input)?;
     Ok((input, data.to_string()))
 }
 
+/// Runs the string parser on the text of the input, then skips what it consumed.
+fn parse_located_string(input: Input) -> IResult<Input, String> {
+    let (rest, string) = parse_string::<Error<&str>>(input.fragment())
+        .map_err(|err| err.map_input(|rest| input.take_from(input.len() - rest.len())))?;
+    Ok((input.take_from(input.len() - rest.len()), string))
+}
+
 #[cfg(test)]
 mod test {
 
     use super::*;
 
+    fn number(number: i64) -> Expression {
+        Expression::Number(Number::from(number), Span::default())
+    }
+    fn variable(name: &str) -> Expression {
+        Expression::Variable(name.to_string(), Span::default())
+    }
+    fn string(string: &str) -> Expression {
+        Expression::String(string.to_string(), Span::default())
+    }
+    fn list(expressions: Vec<Expression>) -> Expression {
+        Expression::List(expressions, Span::default())
+    }
+
     #[test]
     fn parse_expression_inner_test() -> anyhow::Result<()> {
         let input = " 1 1 ";
-        let result = parse_expression_inner(input);
+        let result = unlocated(parse_expression_inner(Input::new_extra(input, None)));
 
-        assert_eq!(
-            result,
-            Ok(("", vec![Expression::Number(1.0), Expression::Number(1.0)]))
-        );
+        assert_eq!(result, Ok(("", vec![number(1), number(1)])));
         Ok(())
     }
     #[test]
@@ -137,14 +604,7 @@ mod test {
 
         assert_eq!(
             result,
-            Ok((
-                "",
-                Expression::List(vec![
-                    Expression::Variable("+".to_string()),
-                    Expression::Number(1.0),
-                    Expression::Number(1.0)
-                ])
-            ))
+            Ok(("", list(vec![variable("+"), number(1), number(1)])))
         );
 
         let input = "(+ 1 (* 2 3 (/ 3 1)))";
@@ -154,18 +614,14 @@ mod test {
             result,
             Ok((
                 "",
-                Expression::List(vec![
-                    Expression::Variable("+".to_string()),
-                    Expression::Number(1.0),
-                    Expression::List(vec![
-                        Expression::Variable("*".to_string()),
-                        Expression::Number(2.0),
-                        Expression::Number(3.0),
-                        Expression::List(vec![
-                            Expression::Variable("/".to_string()),
-                            Expression::Number(3.0),
-                            Expression::Number(1.0)
-                        ])
+                list(vec![
+                    variable("+"),
+                    number(1),
+                    list(vec![
+                        variable("*"),
+                        number(2),
+                        number(3),
+                        list(vec![variable("/"), number(3), number(1)])
                     ])
                 ])
             ))
@@ -175,16 +631,162 @@ mod test {
         let input = "\"hello\"";
         let result = parse_expression(input);
 
-        assert_eq!(result, Ok(("", Expression::String("hello".to_string()))));
+        assert_eq!(result, Ok(("", string("hello"))));
 
         Ok(())
     }
     #[test]
     fn parse_lisp_symbol_test() -> anyhow::Result<()> {
         let input = "test";
-        let result = parse_lisp_variable(input);
+        let result = unlocated(parse_lisp_variable(Input::new_extra(input, None)));
 
         assert_eq!(result, Ok(("", "test".to_string())));
         Ok(())
     }
+    #[test]
+    fn parse_number_test() -> anyhow::Result<()> {
+        let cases = [
+            ("42", "42"),
+            ("-7", "-7"),
+            ("99999999999999999999", "99999999999999999999"),
+            ("6/4", "3/2"),
+            ("-4/2", "-2"),
+            ("1.5", "1.5"),
+            ("2.0", "2.0"),
+            ("1e3", "1000.0"),
+            ("-inf.0", "-inf.0"),
+        ];
+        for (input, expected) in cases {
+            let (rest, expression) = parse_expression(input).map_err(|err| err.to_owned())?;
+            assert_eq!((rest, expression.to_string()), ("", expected.to_string()));
+            assert!(matches!(expression, Expression::Number(..)), "{input}");
+        }
+        for input in ["-", "...", "info", "nan", "+x", "1/0"] {
+            let result = parse_expression(input);
+            assert!(
+                !matches!(result, Ok(("", Expression::Number(..)))),
+                "{input} should not be a number"
+            );
+        }
+        Ok(())
+    }
+    #[test]
+    fn parse_quoted_test() -> anyhow::Result<()> {
+        let input = "`(a ,b ,@c)";
+        let result = parse_expression(input);
+        let form = |name: &str, expression| list(vec![variable(name), expression]);
+
+        assert_eq!(
+            result,
+            Ok((
+                "",
+                form(
+                    "quasiquote",
+                    list(vec![
+                        variable("a"),
+                        form("unquote", variable("b")),
+                        form("unquote-splicing", variable("c")),
+                    ])
+                )
+            ))
+        );
+
+        let input = "'x";
+        let result = parse_expression(input);
+        assert_eq!(result, Ok(("", form("quote", variable("x")))));
+        Ok(())
+    }
+    #[test]
+    fn parse_program_test() -> anyhow::Result<()> {
+        let input = " (define x 1) x (+ x";
+        let result = parse_program(input);
+
+        assert_eq!(
+            result,
+            Ok((
+                "(+ x",
+                vec![
+                    list(vec![variable("define"), variable("x"), number(1)]),
+                    variable("x")
+                ]
+            ))
+        );
+        Ok(())
+    }
+    #[test]
+    fn is_incomplete_test() -> anyhow::Result<()> {
+        assert!(is_incomplete("(define (f x)\n  (+ x"));
+        assert!(is_incomplete("(display \"a ( \\\" b"));
+        assert!(!is_incomplete("(display \"a ( b\")"));
+        assert!(!is_incomplete("(+ 1 2))"));
+        assert!(!is_incomplete(")"));
+        assert!(!is_incomplete("(+ 1 2)) (display"));
+        assert!(is_incomplete("(display \"first\nsecond"));
+        assert!(!is_incomplete("(display \"first\nsecond\")"));
+        assert!(is_incomplete("\"a\" \"b\n(c)"));
+        assert!(!is_incomplete("(+ 1 ; (\n 2)"));
+        assert!(is_incomplete("(+ 1 #| ) |# 2"));
+        assert!(is_incomplete("#| a #| b |# c"));
+        assert!(is_incomplete("#;(a"));
+        Ok(())
+    }
+    #[test]
+    fn parse_comment_test() -> anyhow::Result<()> {
+        let input = "; leading\n(+ 1 ; one\n #| block #| nested |# |# 2 #;(ignored 3))#;4 ";
+        let result = parse_program(input);
+
+        assert_eq!(
+            result,
+            Ok(("", vec![list(vec![variable("+"), number(1), number(2)])]))
+        );
+        Ok(())
+    }
+    #[test]
+    fn span_test() -> anyhow::Result<()> {
+        let file: Rc<str> = Rc::from("test.lisp");
+        let expressions = read_program("(define x 1)\n(f\n  \"λ\" (g x))", &file)
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        let [_, Expression::List(call, span)] = expressions.as_slice() else {
+            anyhow::bail!("expected two lists, got {expressions:?}");
+        };
+        assert_eq!(span.to_string(), "test.lisp:2:1");
+        let positions: Vec<(u32, u32)> = call
+            .iter()
+            .map(|expression| (expression.span().line, expression.span().column))
+            .collect();
+        assert_eq!(positions, vec![(2, 2), (3, 3), (3, 7)]);
+        Ok(())
+    }
+    #[test]
+    fn parse_error_test() -> anyhow::Result<()> {
+        let file: Rc<str> = Rc::from("test.lisp");
+        for (input, expected) in [
+            (
+                "(+ 1 2)\n  (f (g 1)",
+                "test.lisp:2:3: invalid input, expected `)` to close the list",
+            ),
+            (
+                "(+ 1 2))",
+                "test.lisp:1:8: invalid input, expected an expression instead of `)`",
+            ),
+            (
+                "(f\n \"abc)",
+                "test.lisp:2:2: invalid input, expected `\"` to close the string",
+            ),
+            (
+                "1 #| a #| b |#",
+                "test.lisp:1:3: invalid input, expected `|#` to close the comment",
+            ),
+            (
+                "(f ')",
+                "test.lisp:1:4: invalid input, expected an expression after the quote",
+            ),
+        ] {
+            match read_program(input, &file) {
+                Err(err) => assert_eq!(err.to_string(), expected, "{input}"),
+                Ok(expressions) => anyhow::bail!("{input} parsed as {expressions:?}"),
+            }
+        }
+        Ok(())
+    }
 }
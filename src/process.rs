@@ -1,10 +1,56 @@
-use std::collections::HashMap;
+use std::{cmp::Ordering, rc::Rc};
 
 use crate::{
-    environment::Environment,
-    errors::LispComputerError,
-    parse::Expression,
-    value::{Lambda, Value},
+    environment::{Environment, Scope},
+    errors::{Frame, LispComputerError},
+    parse::{Expression, Span, split_dotted_list},
+    value::{Body, Lambda, Number, Params, Value},
+};
+
+mod binding;
+mod boxes;
+mod conditional;
+mod exception;
+mod higher_order;
+mod io;
+mod list;
+mod numeric;
+mod quote;
+mod symbol;
+mod syntax;
+
+pub use binding::{LetStarProcessor, LetValuesProcessor, LetrecProcessor, LetrecStarProcessor};
+pub use boxes::{BoxProcessor, IsBoxProcessor, SetBoxProcessor, UnboxProcessor};
+pub use conditional::{CaseProcessor, UnlessProcessor, WhenProcessor};
+pub use exception::{
+    ErrorObjectIrritantsProcessor, ErrorObjectMessageProcessor, ErrorObjectProcessor,
+    ErrorProcessor, GuardProcessor, Handler, RaiseContinuableProcessor, RaiseProcessor,
+    WithExceptionHandlerProcessor,
+};
+pub use higher_order::{
+    ApplyProcessor, CallWithValuesProcessor, FilterProcessor, FoldLeftProcessor,
+    FoldRightProcessor, ForEachProcessor, MapProcessor, ReduceProcessor, ValuesProcessor,
+};
+pub use io::{DisplayProcessor, NewlineProcessor};
+pub use list::{
+    AppendProcessor, CarProcessor, CdrProcessor, ConsProcessor, IsListProcessor, LengthProcessor,
+    ListProcessor, ListRefProcessor, ListTailProcessor, NullProcessor, PairProcessor,
+    ReverseProcessor,
+};
+pub use numeric::{
+    AbsProcessor, AtanProcessor, CeilingProcessor, CosProcessor, ExactIntegerSqrtProcessor,
+    ExactProcessor, ExpProcessor, ExptProcessor, FloorProcessor, GcdProcessor, InexactProcessor,
+    IsExactIntegerProcessor, IsExactProcessor, IsInexactProcessor, IsIntegerProcessor,
+    IsNumberProcessor, IsRationalProcessor, IsRealProcessor, LcmProcessor, LogProcessor,
+    MaxProcessor, MinProcessor, ModuloProcessor, NumberToStringProcessor, QuotientProcessor,
+    RemainderProcessor, RoundProcessor, SinProcessor, SqrtProcessor, StringToNumberProcessor,
+    TanProcessor, TruncateProcessor,
+};
+pub use quote::{QuasiquoteProcessor, QuoteProcessor};
+pub use symbol::{EqProcessor, StringToSymbolProcessor, SymbolProcessor, SymbolToStringProcessor};
+pub use syntax::{
+    DefineSyntaxProcessor, DefmacroProcessor, LetSyntaxProcessor, MacroexpandOnceProcessor,
+    MacroexpandProcessor, SyntaxRulesProcessor, macroexpand,
 };
 
 pub trait Function<T>
@@ -15,77 +61,232 @@ where
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError>;
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError>;
+    fn name(&self) -> &str;
+}
+
+/// The outcome of a special form: either a finished value or an expression in
+/// tail position. Tail expressions are evaluated by the loop in
+/// [`Expression::eval`] instead of recursing, so tail calls run in constant stack.
+/// `Call` is the body of a lambda, named for backtraces.
+pub enum Tail {
+    Value(Value),
+    Eval(Expression, Rc<Scope>),
+    Call(Option<Rc<str>>, Body, Rc<Scope>),
+}
+
+impl Tail {
+    pub fn eval<T: Environment>(self, env: &T) -> Result<Value, LispComputerError> {
+        match self {
+            Tail::Value(value) => Ok(value),
+            Tail::Eval(expression, scope) => expression.eval_values(env, &scope),
+            // called from a builtin, so there is no call site to report
+            Tail::Call(name, body, scope) => body
+                .iter()
+                .try_fold(Value::Nil, |_, expression| {
+                    expression.eval_values(env, &scope)
+                })
+                .map_err(|err| {
+                    err.in_frame(Frame {
+                        name,
+                        span: Span::default(),
+                    })
+                }),
+        }
+    }
+}
+
+/// A callable value. Unlike the special forms implementing [`Function`], a
+/// procedure receives its arguments already evaluated.
+pub trait Procedure<T>
+where
+    T: Environment,
+{
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError>;
+    /// Calls the procedure in tail position. Procedures that call back into a
+    /// procedure override it to leave that call to the evaluation loop.
+    fn tail_call(&self, args: Vec<Value>, env: &T) -> Result<Tail, LispComputerError> {
+        self.call(args, env).map(Tail::Value)
+    }
     fn name(&self) -> &str;
 }
 
 pub fn process_expression_list<T: Environment>(
     expressions: &[Expression],
     env: &T,
-    variables: &HashMap<&str, Value>,
-) -> Result<Value, LispComputerError> {
+    scope: &Rc<Scope>,
+) -> Result<Tail, LispComputerError> {
     match expressions {
-        [] => Ok(Value::Nil),
-        [Expression::Number(data)] => Ok(Value::Number(*data)),
-        [Expression::Variable(symbol), tail @ ..] => process_variable(symbol, tail, env, variables),
-        [Expression::List(list), tail @ ..] => {
-            if let Value::Lambda(func) = process_expression_list(list, env, variables)? {
-                func.process(tail, env, variables)
-            } else {
-                unimplemented!()
+        [] => Ok(Tail::Value(Value::Nil)),
+        [Expression::Number(data, _)] => Ok(Tail::Value(Value::Number(data.clone()))),
+        [Expression::Variable(symbol, span), tail @ ..] => {
+            process_variable(symbol, span, tail, env, scope)
+        }
+        [head, tail @ ..] => {
+            let procedure = head.eval(env, scope)?;
+            let args = eval_args(tail, env, scope)?;
+            tail_call_procedure(&procedure, args, env)
+        }
+    }
+}
+
+pub fn eval_args<T: Environment>(
+    args: &[Expression],
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Vec<Value>, LispComputerError> {
+    args.iter().map(|arg| arg.eval(env, scope)).collect()
+}
+
+/// Calls a lambda or built-in procedure with already evaluated arguments.
+pub fn apply_procedure<T: Environment>(
+    procedure: &Value,
+    args: Vec<Value>,
+    env: &T,
+) -> Result<Value, LispComputerError> {
+    tail_call_procedure(procedure, args, env)?
+        .eval(env)?
+        .single()
+}
+
+/// Like [`apply_procedure`], but leaves the body of a lambda to the caller's
+/// evaluation loop.
+pub fn tail_call_procedure<T: Environment>(
+    procedure: &Value,
+    args: Vec<Value>,
+    env: &T,
+) -> Result<Tail, LispComputerError> {
+    match procedure {
+        Value::Lambda(lambda) => lambda.tail_call(args, env),
+        Value::Builtin(name) => match env.get_builtin(name.name()) {
+            Some(builtin) => builtin.tail_call(args, env),
+            None => Err(LispComputerError::UnboundFunction(name.to_string())),
+        },
+        other => Err(LispComputerError::NotCallable(other.clone())),
+    }
+}
+
+/// Checks that a built-in procedure received exactly `expected` arguments.
+fn check_arity(name: &str, expected: usize, args: &[Value]) -> Result<(), LispComputerError> {
+    if args.len() != expected {
+        return Err(LispComputerError::ArityMismatch(
+            name.to_string(),
+            expected,
+            args.len(),
+        ));
+    }
+    Ok(())
+}
+
+/// Evaluates the first `cond` clause whose test is true, returning its last
+/// expression in tail position, or `None` when no clause matches. Clauses are
+/// `(test expr...)`, `(test => receiver)` and a final `(else expr...)`.
+fn eval_clauses<T: Environment>(
+    clauses: &[Expression],
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Option<Tail>, LispComputerError> {
+    for (index, clause) in clauses.iter().enumerate() {
+        let Expression::List(clause, _) = clause else {
+            return Err(LispComputerError::InvalidArguments(
+                "cond-clause".to_string(),
+                vec![clause.clone()],
+            ));
+        };
+        let (test, body) = match clause.as_slice() {
+            [Expression::Variable(name, _), body @ ..] if name == "else" => {
+                if index != clauses.len() - 1 || body.is_empty() {
+                    return Err(LispComputerError::MisplacedElse("cond".to_string()));
+                }
+                (Value::Boolean(true), body)
             }
+            [test, body @ ..] => (test.eval(env, scope)?, body),
+            [] => {
+                return Err(LispComputerError::InvalidArguments(
+                    "cond-clause".to_string(),
+                    clause.clone(),
+                ));
+            }
+        };
+        if !test.boolean() {
+            continue;
         }
-        _ => unimplemented!(),
+        return match body {
+            [] => Ok(Some(Tail::Value(test))),
+            [Expression::Variable(arrow, _), receiver] if arrow == "=>" => {
+                let receiver = receiver.eval(env, scope)?;
+                tail_call_procedure(&receiver, vec![test], env).map(Some)
+            }
+            body => eval_body(body, env, scope).map(Some),
+        };
+    }
+    Ok(None)
+}
+
+/// Evaluates the expressions of a body in order, leaving the last one in tail
+/// position.
+fn eval_body<T: Environment>(
+    body: &[Expression],
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Tail, LispComputerError> {
+    let Some((last, body)) = body.split_last() else {
+        return Ok(Tail::Value(Value::Nil));
+    };
+    for expression in body {
+        expression.eval_values(env, scope)?;
     }
+    Ok(Tail::Eval(last.clone(), scope.clone()))
 }
 
 fn process_variable<T: Environment>(
     symbol: &str,
+    span: &Span,
     args: &[Expression],
     env: &T,
-    variables: &HashMap<&str, Value>,
-) -> Result<Value, LispComputerError> {
-    env.process_variable(symbol, args, variables)
+    scope: &Rc<Scope>,
+) -> Result<Tail, LispComputerError> {
+    env.process_variable(symbol, span, args, scope)
 }
 
 pub struct AdditionProcessor;
 
-impl<T: Environment> Function<T> for AdditionProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        let mut sum = 0.0;
+impl<T: Environment> Procedure<T> for AdditionProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let mut sum = Number::from(0);
         let mut result_string = String::new();
 
         for arg in args {
-            match arg.eval(env, variables)? {
+            match arg {
                 Value::Number(n) => {
                     if result_string.is_empty() {
-                        sum += n;
+                        sum = sum.add(&n);
                     } else {
                         return Err(LispComputerError::TypeMismatch2 {
-                            operation: <AdditionProcessor as Function<T>>::name(self).to_string(),
+                            operation: <AdditionProcessor as Procedure<T>>::name(self).to_string(),
                             left: Value::String(result_string),
                             right: Value::Number(n),
                         });
                     }
                 }
                 Value::String(s) => {
-                    if sum == 0.0 {
+                    if sum.is_zero() {
                         result_string.push_str(&s);
                     } else {
                         return Err(LispComputerError::TypeMismatch2 {
-                            operation: <AdditionProcessor as Function<T>>::name(self).to_string(),
+                            operation: <AdditionProcessor as Procedure<T>>::name(self).to_string(),
                             left: Value::Number(sum),
                             right: Value::String(s.to_string()),
                         });
                     }
                 }
-                _ => unimplemented!(),
+                other => {
+                    return Err(LispComputerError::TypeMismatch1 {
+                        operation: <AdditionProcessor as Procedure<T>>::name(self).to_string(),
+                        left: other,
+                    });
+                }
             }
         }
 
@@ -103,38 +304,46 @@ impl<T: Environment> Function<T> for AdditionProcessor {
 
 pub struct DivisionProcessor;
 
-impl<T: Environment> Function<T> for DivisionProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if let Some((first, rest)) = args.split_first() {
-            let initial_value = match first.eval(env, variables)? {
+impl<T: Environment> Procedure<T> for DivisionProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let mut args = args.into_iter();
+        if let Some(first) = args.next() {
+            let initial_value = match first {
                 Value::Number(n) => n,
                 value => {
                     return Err(LispComputerError::TypeMismatch1 {
-                        operation: <DivisionProcessor as Function<T>>::name(self).to_string(),
+                        operation: <DivisionProcessor as Procedure<T>>::name(self).to_string(),
                         left: value,
                     });
                 }
             };
-            let value = rest.iter().try_fold(initial_value, |acc, expr| {
-                let value = expr.eval(env, variables)?;
-                match value {
-                    Value::Number(n) => Ok(acc / n),
-                    value => Err(LispComputerError::TypeMismatch2 {
-                        operation: <DivisionProcessor as Function<T>>::name(self).to_string(),
-                        left: Value::Number(acc),
-                        right: value,
-                    }),
-                }
+            // with a single argument, the reciprocal
+            if args.len() == 0 {
+                return Number::from(1)
+                    .div(&initial_value)
+                    .map(Value::Number)
+                    .ok_or_else(|| {
+                        LispComputerError::DivisionByZero(
+                            <DivisionProcessor as Procedure<T>>::name(self).to_string(),
+                        )
+                    });
+            }
+            let value = args.try_fold(initial_value, |acc, value| match value {
+                Value::Number(n) => acc.div(&n).ok_or_else(|| {
+                    LispComputerError::DivisionByZero(
+                        <DivisionProcessor as Procedure<T>>::name(self).to_string(),
+                    )
+                }),
+                value => Err(LispComputerError::TypeMismatch2 {
+                    operation: <DivisionProcessor as Procedure<T>>::name(self).to_string(),
+                    left: Value::Number(acc),
+                    right: value,
+                }),
             })?;
             Ok(Value::Number(value))
         } else {
             Err(LispComputerError::TypeMismatch1 {
-                operation: <DivisionProcessor as Function<T>>::name(self).to_string(),
+                operation: <DivisionProcessor as Procedure<T>>::name(self).to_string(),
                 left: Value::Nil,
             })
         }
@@ -146,20 +355,16 @@ impl<T: Environment> Function<T> for DivisionProcessor {
 
 pub struct MultiplicationProcessor;
 
-impl<T: Environment> Function<T> for MultiplicationProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        let mut result = 1.0;
+impl<T: Environment> Procedure<T> for MultiplicationProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let mut result = Number::from(1);
         for arg in args {
-            match arg.eval(env, variables)? {
-                Value::Number(num) => result *= num,
+            match arg {
+                Value::Number(num) => result = result.mul(&num),
                 other => {
                     return Err(LispComputerError::TypeMismatch1 {
-                        operation: <MultiplicationProcessor as Function<T>>::name(self).to_string(),
+                        operation: <MultiplicationProcessor as Procedure<T>>::name(self)
+                            .to_string(),
                         left: other,
                     });
                 }
@@ -175,37 +380,34 @@ impl<T: Environment> Function<T> for MultiplicationProcessor {
 
 pub struct SubtractionProcessor;
 
-impl<T: Environment> Function<T> for SubtractionProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if let Some((first, rest)) = args.split_first() {
-            let initial_value = match first.eval(env, variables)? {
+impl<T: Environment> Procedure<T> for SubtractionProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let mut args = args.into_iter();
+        if let Some(first) = args.next() {
+            let initial_value = match first {
                 Value::Number(value) => value,
                 other => {
                     return Err(LispComputerError::TypeMismatch1 {
-                        operation: <SubtractionProcessor as Function<T>>::name(self).to_string(),
+                        operation: <SubtractionProcessor as Procedure<T>>::name(self).to_string(),
                         left: other,
                     });
                 }
             };
-            let value = rest.iter().try_fold(initial_value, |acc, expr| {
-                let value = expr.eval(env, variables)?;
-                match value {
-                    Value::Number(num) => Ok(acc - num),
-                    other => Err(LispComputerError::TypeMismatch1 {
-                        operation: <SubtractionProcessor as Function<T>>::name(self).to_string(),
-                        left: other,
-                    }),
-                }
+            // with a single argument, the negation
+            if args.len() == 0 {
+                return Ok(Value::Number(Number::from(0).sub(&initial_value)));
+            }
+            let value = args.try_fold(initial_value, |acc, value| match value {
+                Value::Number(num) => Ok(acc.sub(&num)),
+                other => Err(LispComputerError::TypeMismatch1 {
+                    operation: <SubtractionProcessor as Procedure<T>>::name(self).to_string(),
+                    left: other,
+                }),
             })?;
             Ok(Value::Number(value))
         } else {
             Err(LispComputerError::TypeMismatch1 {
-                operation: <SubtractionProcessor as Function<T>>::name(self).to_string(),
+                operation: <SubtractionProcessor as Procedure<T>>::name(self).to_string(),
                 left: Value::Nil,
             })
         }
@@ -217,28 +419,24 @@ impl<T: Environment> Function<T> for SubtractionProcessor {
 }
 
 pub struct EqualProcessor;
-impl<T: Environment> Function<T> for EqualProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+impl<T: Environment> Procedure<T> for EqualProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
         if args.len() < 2 {
             return Err(LispComputerError::ArityMismatch(
-                <EqualProcessor as Function<T>>::name(self).to_string(),
+                <EqualProcessor as Procedure<T>>::name(self).to_string(),
                 2,
                 args.len(),
             ));
         }
 
-        let mut evaluated_args = Vec::new();
-        for arg in args {
-            evaluated_args.push(arg.eval(env, variables)?);
-        }
-
-        for pair in evaluated_args.windows(2) {
-            if pair[0] != pair[1] {
+        for pair in args.windows(2) {
+            let equal = match (&pair[0], &pair[1]) {
+                (Value::Number(left), Value::Number(right)) => {
+                    left.compare(right) == Some(Ordering::Equal)
+                }
+                (left, right) => left == right,
+            };
+            if !equal {
                 return Ok(Value::Boolean(false));
             }
         }
@@ -250,37 +448,34 @@ impl<T: Environment> Function<T> for EqualProcessor {
     }
 }
 
-pub struct GreaterThanProcessor;
-impl<T: Environment> Function<T> for GreaterThanProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if args.len() < 2 {
-            return Err(LispComputerError::ArityMismatch(
-                <GreaterThanProcessor as Function<T>>::name(self).to_string(),
-                2,
-                args.len(),
-            ));
-        }
+/// Checks the arguments of a numeric comparison and extracts the numbers.
+fn comparison_args(name: &str, args: Vec<Value>) -> Result<Vec<Number>, LispComputerError> {
+    if args.len() < 2 {
+        return Err(LispComputerError::ArityMismatch(
+            name.to_string(),
+            2,
+            args.len(),
+        ));
+    }
+    args.into_iter()
+        .map(|arg| match arg {
+            Value::Number(n) => Ok(n),
+            other => Err(LispComputerError::TypeMismatch1 {
+                operation: name.to_string(),
+                left: other,
+            }),
+        })
+        .collect()
+}
 
-        let mut evaluated_args = Vec::new();
-        for arg in args {
-            match arg.eval(env, variables)? {
-                Value::Number(n) => evaluated_args.push(n),
-                other => {
-                    return Err(LispComputerError::TypeMismatch1 {
-                        operation: <GreaterThanProcessor as Function<T>>::name(self).to_string(),
-                        left: other,
-                    });
-                }
-            }
-        }
+pub struct GreaterThanProcessor;
+impl<T: Environment> Procedure<T> for GreaterThanProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let evaluated_args =
+            comparison_args(<GreaterThanProcessor as Procedure<T>>::name(self), args)?;
 
         for pair in evaluated_args.windows(2) {
-            if pair[0] <= pair[1] {
+            if pair[0].compare(&pair[1]) != Some(Ordering::Greater) {
                 return Ok(Value::Boolean(false));
             }
         }
@@ -295,35 +490,13 @@ impl<T: Environment> Function<T> for GreaterThanProcessor {
 
 pub struct LessThanProcessor;
 
-impl<T: Environment> Function<T> for LessThanProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if args.len() < 2 {
-            return Err(LispComputerError::InvalidArguments(
-                <LessThanProcessor as Function<T>>::name(self).to_string(),
-                args.to_vec(),
-            ));
-        }
-
-        let mut evaluated_args = Vec::new();
-        for arg in args {
-            match arg.eval(env, variables)? {
-                Value::Number(n) => evaluated_args.push(n),
-                other => {
-                    return Err(LispComputerError::TypeMismatch1 {
-                        operation: <LessThanProcessor as Function<T>>::name(self).to_string(),
-                        left: other,
-                    });
-                }
-            }
-        }
+impl<T: Environment> Procedure<T> for LessThanProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let evaluated_args =
+            comparison_args(<LessThanProcessor as Procedure<T>>::name(self), args)?;
 
         for pair in evaluated_args.windows(2) {
-            if pair[0] >= pair[1] {
+            if pair[0].compare(&pair[1]) != Some(Ordering::Less) {
                 return Ok(Value::Boolean(false));
             }
         }
@@ -338,34 +511,15 @@ impl<T: Environment> Function<T> for LessThanProcessor {
 
 pub struct GreaterEqualProcessor;
 
-impl<T: Environment> Function<T> for GreaterEqualProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if args.len() < 2 {
-            return Err(LispComputerError::InvalidArguments(
-                <GreaterEqualProcessor as Function<T>>::name(self).to_string(),
-                args.to_vec(),
-            ));
-        }
-        let mut evaluated_args = Vec::new();
-
-        for arg in args {
-            match arg.eval(env, variables)? {
-                Value::Number(n) => evaluated_args.push(n),
-                other => {
-                    return Err(LispComputerError::TypeMismatch1 {
-                        operation: <GreaterEqualProcessor as Function<T>>::name(self).to_string(),
-                        left: other,
-                    });
-                }
-            }
-        }
+impl<T: Environment> Procedure<T> for GreaterEqualProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let evaluated_args =
+            comparison_args(<GreaterEqualProcessor as Procedure<T>>::name(self), args)?;
         for pair in evaluated_args.windows(2) {
-            if pair[0] < pair[1] {
+            if !matches!(
+                pair[0].compare(&pair[1]),
+                Some(Ordering::Greater | Ordering::Equal)
+            ) {
                 return Ok(Value::Boolean(false));
             }
         }
@@ -379,33 +533,15 @@ impl<T: Environment> Function<T> for GreaterEqualProcessor {
 
 pub struct LessEqualProcessor;
 
-impl<T: Environment> Function<T> for LessEqualProcessor {
-    fn process(
-        &self,
-        args: &[Expression],
-        env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if args.len() < 2 {
-            return Err(LispComputerError::InvalidArguments(
-                <LessEqualProcessor as Function<T>>::name(self).to_string(),
-                args.to_vec(),
-            ));
-        }
-        let mut evaluated_args = Vec::new();
-        for arg in args {
-            match arg.eval(env, variables)? {
-                Value::Number(n) => evaluated_args.push(n),
-                other => {
-                    return Err(LispComputerError::TypeMismatch1 {
-                        operation: <LessEqualProcessor as Function<T>>::name(self).to_string(),
-                        left: other,
-                    });
-                }
-            }
-        }
+impl<T: Environment> Procedure<T> for LessEqualProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let evaluated_args =
+            comparison_args(<LessEqualProcessor as Procedure<T>>::name(self), args)?;
         for pair in evaluated_args.windows(2) {
-            if pair[0] > pair[1] {
+            if !matches!(
+                pair[0].compare(&pair[1]),
+                Some(Ordering::Less | Ordering::Equal)
+            ) {
                 return Ok(Value::Boolean(false));
             }
         }
@@ -424,19 +560,25 @@ impl<T: Environment> Function<T> for IfProcessor {
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         match args {
             [condition, then_branch, else_branch] => {
-                let condition = condition.eval(env, variables)?.boolean();
-                match condition {
-                    true => then_branch.eval(env, variables),
-                    false => else_branch.eval(env, variables),
-                }
+                let condition = condition.eval(env, scope)?.boolean();
+                let branch = match condition {
+                    true => then_branch,
+                    false => else_branch,
+                };
+                Ok(Tail::Eval(branch.clone(), scope.clone()))
             }
+            // without an else branch a false condition gives an unspecified value
+            [condition, then_branch] => match condition.eval(env, scope)?.boolean() {
+                true => Ok(Tail::Eval(then_branch.clone(), scope.clone())),
+                false => Ok(Tail::Value(Value::Nil)),
+            },
             _ => Err(LispComputerError::ArityMismatch(
                 <IfProcessor as Function<T>>::name(self).to_string(),
-                3,
+                if args.len() < 2 { 2 } else { 3 },
                 args.len(),
             )),
         }
@@ -447,6 +589,23 @@ impl<T: Environment> Function<T> for IfProcessor {
     }
 }
 
+pub struct BeginProcessor;
+
+impl<T: Environment> Function<T> for BeginProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        eval_body(args, env, scope)
+    }
+
+    fn name(&self) -> &str {
+        "begin"
+    }
+}
+
 pub struct OrProcessor;
 
 impl<T: Environment> Function<T> for OrProcessor {
@@ -454,19 +613,20 @@ impl<T: Environment> Function<T> for OrProcessor {
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        let mut last_value = Value::Boolean(false);
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let Some((last, args)) = args.split_last() else {
+            return Ok(Tail::Value(Value::Boolean(false)));
+        };
 
         for arg in args {
-            let value = arg.eval(env, variables)?;
+            let value = arg.eval(env, scope)?;
             if value.boolean() {
-                return Ok(value);
+                return Ok(Tail::Value(value));
             }
-            last_value = value;
         }
 
-        Ok(last_value)
+        Ok(Tail::Eval(last.clone(), scope.clone()))
     }
 
     fn name(&self) -> &str {
@@ -481,19 +641,20 @@ impl<T: Environment> Function<T> for AndProcessor {
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        let mut last_value = Value::Boolean(true);
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let Some((last, args)) = args.split_last() else {
+            return Ok(Tail::Value(Value::Boolean(true)));
+        };
 
         for arg in args {
-            let value = arg.eval(env, variables)?;
+            let value = arg.eval(env, scope)?;
             if !value.boolean() {
-                return Ok(value);
+                return Ok(Tail::Value(value));
             }
-            last_value = value;
         }
 
-        Ok(last_value)
+        Ok(Tail::Eval(last.clone(), scope.clone()))
     }
 
     fn name(&self) -> &str {
@@ -508,35 +669,77 @@ impl<T: Environment> Function<T> for CondProcessor {
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
-        if let Some((last, args)) = args.split_last() {
-            for arg in args {
-                if let Expression::List(inner_args) = arg {
-                    if let [condition, result] = inner_args.as_slice() {
-                        let condition_value = condition.eval(env, variables)?;
-                        if condition_value.boolean() {
-                            return result.eval(env, variables);
-                        }
-                    }
-                }
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        // without a matching clause the value is unspecified
+        Ok(eval_clauses(args, env, scope)?.unwrap_or(Tail::Value(Value::Nil)))
+    }
+
+    fn name(&self) -> &str {
+        "cond"
+    }
+}
+
+/// Reads the formals of a lambda: a list of names, which may be followed by
+/// optional `(name default)` parameters and a dotted rest parameter, or a single
+/// name bound to the list of all the arguments.
+fn lambda_params(formals: &Expression) -> Result<Params, LispComputerError> {
+    match formals {
+        Expression::Variable(rest, _) => Ok(Params {
+            rest: Some(rest.clone()),
+            ..Params::default()
+        }),
+        Expression::List(formals, _) => {
+            let (formals, rest) = split_dotted_list(formals)?;
+            formal_params(formals, rest)
+        }
+        _ => Err(LispComputerError::InvalidArguments(
+            "lambda-params".to_string(),
+            vec![formals.clone()],
+        )
+        .at(formals.span())),
+    }
+}
+
+fn formal_params(
+    formals: &[Expression],
+    rest: Option<&Expression>,
+) -> Result<Params, LispComputerError> {
+    let invalid = |param: &Expression| {
+        LispComputerError::InvalidArguments("lambda-params".to_string(), formals.to_vec())
+            .at(param.span())
+    };
+    let mut params = Params::default();
+    for param in formals {
+        match param {
+            // required parameters come before the optional ones
+            Expression::Variable(name, _) if params.optional.is_empty() => {
+                params.required.push(name.clone())
             }
-            if let Expression::List(inner_args) = last {
-                if let [Expression::Variable(name), result] = inner_args.as_slice() {
-                    if name == "else" {
-                        return result.eval(env, variables);
-                    }
+            Expression::List(optional, _) => match optional.as_slice() {
+                [Expression::Variable(name, _), default] => {
+                    params.optional.push((name.clone(), default.clone()))
                 }
-            }
+                _ => return Err(invalid(param)),
+            },
+            _ => return Err(invalid(param)),
         }
-        Err(LispComputerError::InvalidArguments(
-            <CondProcessor as Function<T>>::name(self).to_string(),
-            args.to_vec(),
-        ))
     }
+    params.rest = match rest {
+        Some(Expression::Variable(name, _)) => Some(name.clone()),
+        Some(other) => return Err(invalid(other)),
+        None => None,
+    };
+    Ok(params)
+}
 
-    fn name(&self) -> &str {
-        "cond"
+/// Binds a definition: globally at top level, otherwise in the frame of the
+/// enclosing body.
+fn define_variable<T: Environment>(name: &str, value: Value, env: &T, scope: &Scope) {
+    if scope.is_top_level() {
+        env.set_variable(name.to_string(), value);
+    } else {
+        scope.insert(name.to_string(), value);
     }
 }
 
@@ -547,35 +750,28 @@ impl<T: Environment> Function<T> for DefineProcessor {
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         match args {
-            [Expression::Variable(name), value] => {
-                let value = value.eval(env, variables)?;
-                env.set_variable(name.to_string(), value);
-                Ok(Value::Nil)
+            [Expression::Variable(name, _), value] => {
+                let value = value.eval(env, scope)?;
+                define_variable(name, value, env, scope);
+                Ok(Tail::Value(Value::Nil))
             }
-            [Expression::List(params), Expression::List(body)] => match params.as_slice() {
-                [Expression::Variable(name), tail @ ..] => {
-                    let params = tail
-                        .iter()
-                        .map(|param| match param {
-                            Expression::Variable(name) => Ok(name.clone()),
-                            _ => Err(LispComputerError::InvalidArguments(
-                                "lambda-params".to_string(),
-                                params.clone(),
-                            )),
-                        })
-                        .collect::<Result<Vec<String>, LispComputerError>>()?;
-                    let lambda = Lambda::new(params, body.clone());
-                    env.set_variable(name.to_string(), Value::Lambda(lambda));
-                    Ok(Value::Nil)
+            [Expression::List(signature, _), body @ ..] if !body.is_empty() => {
+                match split_dotted_list(signature)? {
+                    ([Expression::Variable(name, _), formals @ ..], rest) => {
+                        let params = formal_params(formals, rest)?;
+                        let lambda = Lambda::new(params, body.into(), scope).with_name(name);
+                        define_variable(name, Value::Lambda(Rc::new(lambda)), env, scope);
+                        Ok(Tail::Value(Value::Nil))
+                    }
+                    _ => Err(LispComputerError::InvalidArguments(
+                        <DefineProcessor as Function<T>>::name(self).to_string(),
+                        args.to_vec(),
+                    )),
                 }
-                _ => Err(LispComputerError::InvalidArguments(
-                    <DefineProcessor as Function<T>>::name(self).to_string(),
-                    args.to_vec(),
-                )),
-            },
+            }
             _ => Err(LispComputerError::InvalidArguments(
                 <DefineProcessor as Function<T>>::name(self).to_string(),
                 args.to_vec(),
@@ -588,30 +784,56 @@ impl<T: Environment> Function<T> for DefineProcessor {
     }
 }
 
+/// `(set! name value)` assigns the nearest binding of `name`.
+pub struct SetProcessor;
+
+impl<T: Environment> Function<T> for SetProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        match args {
+            [Expression::Variable(name, span), value] => {
+                let value = value.eval(env, scope)?;
+                if !env.assign_variable(name, value, scope) {
+                    return Err(LispComputerError::NotFoundVariable(name.to_string()).at(span));
+                }
+                Ok(Tail::Value(Value::Nil))
+            }
+            _ => Err(LispComputerError::InvalidArguments(
+                <SetProcessor as Function<T>>::name(self).to_string(),
+                args.to_vec(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "set!"
+    }
+}
+
 pub struct LambdaProcessor;
 impl<T: Environment> Function<T> for LambdaProcessor {
     fn process(
         &self,
         args: &[Expression],
         _env: &T,
-        _variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         match args {
-            [Expression::List(params), Expression::List(body)] => {
-                let params = params
-                    .iter()
-                    .map(|param| match param {
-                        Expression::Variable(name) => Ok(name.clone()),
-                        _ => Err(LispComputerError::InvalidArguments(
-                            "lambda-params".to_string(),
-                            params.clone(),
-                        )),
-                    })
-                    .collect::<Result<Vec<String>, LispComputerError>>()?;
+            [
+                formals @ (Expression::List(..) | Expression::Variable(..)),
+                body @ ..,
+            ] if !body.is_empty() => {
+                let params = lambda_params(formals)?;
 
-                let body = body.clone();
+                let body = body.into();
 
-                Ok(Value::Lambda(Lambda::new(params, body)))
+                Ok(Tail::Value(Value::Lambda(Rc::new(Lambda::new(
+                    params, body, scope,
+                )))))
             }
             _ => Err(LispComputerError::InvalidArguments(
                 <LambdaProcessor as Function<T>>::name(self).to_string(),
@@ -625,24 +847,64 @@ impl<T: Environment> Function<T> for LambdaProcessor {
     }
 }
 
+/// `(case-lambda (formals body...)...)` makes a procedure that calls the first
+/// clause whose formals accept the number of arguments.
+pub struct CaseLambdaProcessor;
+impl<T: Environment> Function<T> for CaseLambdaProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        _env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let clauses = args
+            .iter()
+            .map(|clause| match clause {
+                Expression::List(clause, _) => match clause.as_slice() {
+                    [
+                        formals @ (Expression::List(..) | Expression::Variable(..)),
+                        body @ ..,
+                    ] if !body.is_empty() => Ok((lambda_params(formals)?, body.into())),
+                    _ => Err(LispComputerError::InvalidArguments(
+                        <CaseLambdaProcessor as Function<T>>::name(self).to_string(),
+                        clause.clone(),
+                    )),
+                },
+                _ => Err(LispComputerError::InvalidArguments(
+                    <CaseLambdaProcessor as Function<T>>::name(self).to_string(),
+                    args.to_vec(),
+                )),
+            })
+            .collect::<Result<Vec<(Params, Body)>, LispComputerError>>()?;
+        Ok(Tail::Value(Value::Lambda(Rc::new(Lambda::with_clauses(
+            clauses, scope,
+        )))))
+    }
+
+    fn name(&self) -> &str {
+        "case-lambda"
+    }
+}
+
 pub struct LetProcessor;
 impl<T: Environment> Function<T> for LetProcessor {
     fn process(
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         fn get_lambda_from(
-            bindings: &Vec<Expression>,
-            body: &Vec<Expression>,
+            bindings: &[Expression],
+            body: &[Expression],
+            scope: &Rc<Scope>,
         ) -> Result<(Lambda, Vec<Expression>), LispComputerError> {
             let mut params = Vec::new();
             let mut lambda_args = Vec::new();
             for binding in bindings {
                 match binding {
-                    Expression::List(binding) => match binding.as_slice() {
-                        [Expression::Variable(name), value] => {
+                    Expression::List(binding, _) => match binding.as_slice() {
+                        [Expression::Variable(name, _), value] => {
                             params.push(name.to_string());
                             lambda_args.push(value.clone());
                         }
@@ -656,35 +918,33 @@ impl<T: Environment> Function<T> for LetProcessor {
                     _ => {
                         return Err(LispComputerError::InvalidArguments(
                             "let-bindings".to_string(),
-                            bindings.clone(),
+                            bindings.to_vec(),
                         ));
                     }
                 }
             }
 
-            let lambda = Lambda::new(params, body.clone());
+            let lambda = Lambda::new(params, body.into(), scope);
             Ok((lambda, lambda_args))
         }
         match args {
             // let
-            [Expression::List(bindings), Expression::List(body)] => {
-                let (lambda, lambda_args) = get_lambda_from(bindings, body)?;
-                lambda.process(&lambda_args, env, variables)
+            [Expression::List(bindings, _), body @ ..] if !body.is_empty() => {
+                let (lambda, lambda_args) = get_lambda_from(bindings, body, scope)?;
+                lambda.tail_call(eval_args(&lambda_args, env, scope)?, env)
             }
             // let naming
-            [
-                Expression::NamingList(name, bindings),
-                Expression::List(body),
-            ]
+            [Expression::NamingList(name, bindings, _), body @ ..]
             | [
-                Expression::Variable(name),
-                Expression::List(bindings),
-                Expression::List(body),
-            ] => {
-                let mut variables = variables.clone();
-                let (lambda, lambda_args) = get_lambda_from(bindings, body)?;
-                variables.insert(name, Value::Lambda(lambda.clone()));
-                lambda.process(&lambda_args, env, &variables)
+                Expression::Variable(name, _),
+                Expression::List(bindings, _),
+                body @ ..,
+            ] if !body.is_empty() => {
+                let loop_scope = Scope::new(scope);
+                let (lambda, lambda_args) = get_lambda_from(bindings, body, &loop_scope)?;
+                let lambda = Rc::new(lambda.with_name(name));
+                loop_scope.insert(name.to_string(), Value::Lambda(lambda.clone()));
+                lambda.tail_call(eval_args(&lambda_args, env, scope)?, env)
             }
             _ => Err(LispComputerError::InvalidArguments(
                 <LetProcessor as Function<T>>::name(self).to_string(),
@@ -708,31 +968,36 @@ where
         &self,
         args: &[Expression],
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<Value, LispComputerError> {
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
         struct DoStep<'a> {
             name: &'a str,
-            step_expr: &'a Expression,
+            step_expr: Option<&'a Expression>,
         }
         struct DoTest<'a> {
             test_expr: &'a Expression,
-            result_expr: &'a Expression,
+            result_exprs: &'a [Expression],
         }
         match args {
             [
-                Expression::List(bindings),
-                Expression::List(test),
+                Expression::List(bindings, _),
+                Expression::List(test, _),
                 bodys @ ..,
             ] => {
-                let mut new_variables = variables.clone();
+                let mut do_scope = Scope::new(scope);
                 let mut steps = Vec::new();
                 for binding in bindings {
                     match binding {
-                        Expression::List(list) => {
-                            if let [Expression::Variable(name), value, step_expr] = list.as_slice()
+                        Expression::List(list, _) => {
+                            if let [Expression::Variable(name, _), value, step @ ..] =
+                                list.as_slice()
+                                && step.len() <= 1
                             {
-                                new_variables.insert(name, value.eval(env, variables)?);
-                                steps.push(DoStep { name, step_expr });
+                                do_scope.insert(name.to_string(), value.eval(env, scope)?);
+                                steps.push(DoStep {
+                                    name,
+                                    step_expr: step.first(),
+                                });
                             } else {
                                 return Err(LispComputerError::InvalidArguments(
                                     <Self as Function<T>>::name(self).to_string(),
@@ -749,9 +1014,9 @@ where
                     }
                 }
                 let do_test = match test.as_slice() {
-                    [test_expr, result_expr] => DoTest {
+                    [test_expr, result_exprs @ ..] => DoTest {
                         test_expr,
-                        result_expr,
+                        result_exprs,
                     },
                     _ => {
                         return Err(LispComputerError::InvalidArguments(
@@ -761,20 +1026,30 @@ where
                     }
                 };
                 loop {
-                    if do_test.test_expr.eval(env, &new_variables)?.boolean() {
-                        return do_test.result_expr.eval(env, &new_variables);
+                    if do_test.test_expr.eval(env, &do_scope)?.boolean() {
+                        let Some((last, results)) = do_test.result_exprs.split_last() else {
+                            return Ok(Tail::Value(Value::Nil));
+                        };
+                        for result in results {
+                            result.eval(env, &do_scope)?;
+                        }
+                        return Ok(Tail::Eval(last.clone(), do_scope));
                     }
                     for body in bodys {
-                        body.eval(env, &new_variables)?;
+                        body.eval(env, &do_scope)?;
+                    }
+                    // each iteration gets fresh bindings, so closures made in the body keep
+                    // the values of their own iteration
+                    let next_scope = Scope::new(scope);
+                    for step in &steps {
+                        // a variable without a step keeps its value
+                        let new_value = match step.step_expr {
+                            Some(step_expr) => step_expr.eval(env, &do_scope)?,
+                            None => do_scope.get(step.name).unwrap_or(Value::Nil),
+                        };
+                        next_scope.insert(step.name.to_string(), new_value);
                     }
-                    let values = steps
-                        .iter()
-                        .map::<Result<_, LispComputerError>, _>(|step| {
-                            let new_value = step.step_expr.eval(env, &new_variables)?;
-                            Ok((step.name, new_value))
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
-                    new_variables.extend(values);
+                    do_scope = next_scope;
                 }
             }
             _ => Err(LispComputerError::InvalidArguments(
@@ -787,3 +1062,751 @@ where
         "do"
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    use crate::{
+        environment::{GlobalEnvironment, Scope},
+        parse::{parse_expression, parse_program},
+        value::{Number, Value},
+    };
+
+    /// Collects what `display` and `newline` write.
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Output {
+        fn text(&self) -> String {
+            String::from_utf8_lossy(&self.0.borrow()).to_string()
+        }
+    }
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn eval_all(env: &GlobalEnvironment, inputs: &[&str]) -> anyhow::Result<Value> {
+        let scope = Rc::new(Scope::default());
+        let mut result = Value::Nil;
+        for input in inputs {
+            let (_, expression) = parse_expression(input).map_err(|err| err.to_owned())?;
+            result = expression
+                .eval(env, &scope)
+                .map_err(|err| anyhow::anyhow!("{err}"))?;
+        }
+        Ok(result)
+    }
+
+    #[test]
+    fn lambda_closure_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(
+            &env,
+            &[
+                "(define (make-adder n) (lambda (x) (+ x n)))",
+                "(define add5 (make-adder 5))",
+                "(add5 10)",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(15)));
+        Ok(())
+    }
+
+    #[test]
+    fn scope_shadowing_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(define x 1)", "(define (f x) (* x 10))", "(f 2)"])?;
+        assert_eq!(result, Value::Number(Number::from(20)));
+        let result = eval_all(
+            &env,
+            &["(let loop ((i 0)) (if (< i 3) ((lambda () (loop (+ i 1)))) i))"],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn list_library_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(append (list 1 2) (reverse (list 4 3)) 5)"])?;
+        assert_eq!(format!("{result}"), "(1 2 3 4 . 5)");
+        let result = eval_all(&env, &["(list-ref (cons 1 (list 2 3)) 2)"])?;
+        assert_eq!(result, Value::Number(Number::from(3)));
+        let result = eval_all(&env, &["(length (cdr (list 1 2 3)))"])?;
+        assert_eq!(result, Value::Number(Number::from(2)));
+        // long lists are compared and dropped without recursing down the spine
+        let result = eval_all(
+            &env,
+            &[
+                "(define (iota n) (let loop ((i 0) (acc (list))) (if (= i n) acc (loop (+ i 1) (cons i acc)))))",
+                "(= (iota 100000) (iota 100000))",
+            ],
+        )?;
+        assert_eq!(result, Value::Boolean(true));
+        Ok(())
+    }
+
+    #[test]
+    fn quasiquote_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(define x 5)", "`(a ,x ,@(list 1 2) . ,x)"])?;
+        assert_eq!(format!("{result}"), "(a 5 1 2 . 5)");
+        let result = eval_all(&env, &["'(1 (b . \"c\"))"])?;
+        assert_eq!(format!("{result}"), "(1 (b . \"c\"))");
+        Ok(())
+    }
+
+    #[test]
+    fn symbol_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(eq? 'abc (string->symbol \"abc\"))"])?;
+        assert_eq!(result, Value::Boolean(true));
+        let result = eval_all(&env, &["(symbol->string (car '(tag 1)))"])?;
+        assert_eq!(result, Value::String("tag".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn builtin_value_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(define plus +)", "(plus 1 2)"])?;
+        assert_eq!(result, Value::Number(Number::from(3)));
+        let result = eval_all(&env, &["(define (apply2 f a b) (f a b))", "(apply2 < 1 2)"])?;
+        assert_eq!(result, Value::Boolean(true));
+        let result = eval_all(&env, &["((lambda (list) (list 1)) car)"]);
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn higher_order_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(map + '(1 2 3) '(10 20))"])?;
+        assert_eq!(format!("{result}"), "(11 22)");
+        let result = eval_all(&env, &["(filter (lambda (x) (> x 1)) '(1 2 3))"])?;
+        assert_eq!(format!("{result}"), "(2 3)");
+        let result = eval_all(&env, &["(fold-left - 0 '(1 2 3))"])?;
+        assert_eq!(result, Value::Number(Number::from(-6)));
+        let result = eval_all(&env, &["(fold-right cons nil '(1 2 3))"])?;
+        assert_eq!(format!("{result}"), "(1 2 3)");
+        let result = eval_all(&env, &["(apply + 1 2 '(3 4))"])?;
+        assert_eq!(result, Value::Number(Number::from(10)));
+        Ok(())
+    }
+
+    #[test]
+    fn tail_call_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(
+            &env,
+            &[
+                "(define (count n) (cond ((= n 0) 'done) (else (and #t (count (- n 1))))))",
+                "(count 100000)",
+            ],
+        )?;
+        assert_eq!(format!("{result}"), "done");
+        let result = eval_all(
+            &env,
+            &["(let loop ((i 0)) (if (< i 100000) (begin i (loop (+ i 1))) i))"],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(100000)));
+        let result = eval_all(
+            &env,
+            &[
+                "(define (f n) (apply (lambda (x) (if (= x 0) 0 (f (- x 1)))) (list n)))",
+                "(f 100000)",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(0)));
+        Ok(())
+    }
+
+    #[test]
+    fn typed_error_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        for input in ["(\"a\" 1)", "((list 1) 2)", "(+ 1 #t)", "(+ 1 car)"] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn located_error_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(&env, &["(define (f x) (car x))", "(+ 1 (f 1))"]);
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("1:15: Operation car mismatch: get 1".to_string())
+        );
+        let result = eval_all(&env, &["(list 1\n  undefined)"]);
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("2:3: Variable not found: undefined".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn backtrace_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let scope = Rc::new(Scope::default());
+        let program = "(define (inner x) (car x))\n\
+                       (define (outer x) (+ 1 (inner x)))\n\
+                       (map (lambda (x) (outer x)) (list 1))";
+        let (_, expressions) = parse_program(program).map_err(|err| err.to_owned())?;
+        let mut result = Ok(Value::Nil);
+        for expression in expressions {
+            result = expression.eval(&env, &scope);
+        }
+        let Err(err) = result else {
+            anyhow::bail!("expected an error, got {result:?}");
+        };
+        let frames: Vec<String> = err.backtrace().iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            frames,
+            vec![
+                "in inner called at 2:24",
+                "in outer called at 3:18",
+                "in <lambda>"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn exception_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let cases = [
+            (
+                "(guard (e ((error-object? e) (list (error-object-message e) (error-object-irritants e)))) (error \"bad record\" 1 2))",
+                "(\"bad record\" (1 2))",
+            ),
+            ("(guard (e ((symbol? e) e)) (raise 'oops))", "oops"),
+            ("(guard (e (#t (error-object-irritants e))) (car 5))", "(5)"),
+            (
+                "(guard (e ((car e) => cdr)) (raise (list (cons 1 2))))",
+                "2",
+            ),
+            (
+                "(with-exception-handler (lambda (c) (+ c 1)) (lambda () (* 2 (raise-continuable 20))))",
+                "42",
+            ),
+            (
+                "(guard (e (#t (list 'outer e))) (guard (e ((null? e) 'inner)) (raise 'x)))",
+                "(outer x)",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(eval_all(&env, &[input])?.to_string(), expected, "{input}");
+        }
+        let result = eval_all(&env, &["(guard (e ((null? e) 'caught)) (raise 'x))"]);
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("1:32: Uncaught exception: x".to_string())
+        );
+        let result = eval_all(&env, &["(guard (e (#t 'h)) (define zz 5) zz)", "zz"]);
+        assert_eq!(
+            result.map_err(|err| err.to_string()),
+            Err("1:1: Variable not found: zz".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn syntax_rules_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax my-or (syntax-rules () ((_) #f) ((_ e) e) ((_ e r ...) (let ((t e)) (if t t (my-or r ...))))))",
+                "(define t 5)",
+                "(my-or #f t)",
+            ],
+        )?;
+        // the `t` bound by the expansion does not capture the user's `t`
+        assert_eq!(result, Value::Number(Number::from(5)));
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax for (syntax-rules (in) ((_ x in items body) (map (lambda (x) body) items))))",
+                "(for y in (list 1 2 3) (* y y))",
+            ],
+        )?;
+        assert_eq!(result.to_string(), "(1 4 9)");
+        let result = eval_all(
+            &env,
+            &[
+                "(let-syntax ((pairs (syntax-rules () ((_ (a b) ...) (list (cons a b) ...))))) (pairs (1 2) (3 4)))",
+            ],
+        )?;
+        assert_eq!(result.to_string(), "((1 . 2) (3 . 4))");
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax my-list (syntax-rules () ((_ . args) (list . args))))",
+                "(list (my-list) (my-list 1 2))",
+            ],
+        )?;
+        assert_eq!(result.to_string(), "(nil (1 2))");
+        // quoted data is not renamed, bindings are renamed only within their scope
+        let cases = [
+            (
+                "(define-syntax q (syntax-rules () ((_) (let ((x 1)) (list x '(x) `(x ,x))))))",
+                "(q)",
+                "(1 (x) (x 1))",
+            ),
+            (
+                "(define-syntax s (syntax-rules () ((_ e) (let ((x 1)) (let* ((x (+ x e)) (y x)) (list x y))))))",
+                "(s 10)",
+                "(11 11)",
+            ),
+            (
+                "(define-syntax fv (syntax-rules () ((_) (list x (let ((x 2)) x)))))",
+                "(begin (define x 1) (fv))",
+                "(1 2)",
+            ),
+            (
+                "(define-syntax local (syntax-rules () ((_ e) ((lambda () (define x e) (list x 'x))))))",
+                "(let ((x 3)) (local (+ x 1)))",
+                "(4 x)",
+            ),
+        ];
+        for (definition, input, expected) in cases {
+            let result = eval_all(&env, &[definition, input])?;
+            assert_eq!(result.to_string(), expected, "{input}");
+        }
+        // the aliases of renamed identifiers cannot be read, so they never collide
+        // with the user's
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax dbl (syntax-rules () ((_ e) (let ((x e)) (+ x x)))))",
+                "(symbol->string (car (car (car (cdr (macroexpand '(dbl 1)))))))",
+            ],
+        )?;
+        let Value::String(alias) = result else {
+            anyhow::bail!("not a string: {result}");
+        };
+        assert!(alias.starts_with("x "), "{alias}");
+        assert_eq!(parse_program(&alias).map(|(_, forms)| forms.len()), Ok(2));
+        // top-level definitions made by a template are seen by the user
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax m2 (syntax-rules () ((_) (define helper 42))))",
+                "(m2)",
+                "helper",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(42)));
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax def-macro (syntax-rules () ((_) (define-syntax made (syntax-rules () ((_ x) (list x x)))))))",
+                "(def-macro)",
+                "(made 7)",
+            ],
+        )?;
+        assert_eq!(result.to_string(), "(7 7)");
+        assert!(eval_all(&env, &["(for y on (list 1) y)"]).is_err());
+        assert!(eval_all(&env, &["(pairs (1 2))"]).is_err());
+        assert!(eval_all(&env, &["(define-syntax foo (syntax-rules () (() 1)))"]).is_err());
+        assert!(eval_all(&env, &["(define-syntax foo (syntax-rules () (x 1)))"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn defmacro_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(
+            &env,
+            &[
+                "(defmacro swap-args (call) (list (car call) (car (cdr (cdr call))) (car (cdr call))))",
+                "(swap-args (- 1 10))",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(9)));
+        let result = eval_all(
+            &env,
+            &[
+                "(define-syntax twice (syntax-rules () ((_ e) (swap-args (+ e e)))))",
+                "(list (macroexpand-1 '(twice x)) (macroexpand '(twice x)) (macroexpand '(car x)))",
+            ],
+        )?;
+        assert_eq!(result.to_string(), "((swap-args (+ x x)) (+ x x) (car x))");
+        Ok(())
+    }
+
+    #[test]
+    fn variadic_lambda_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let cases = [
+            ("((lambda (a . rest) (list a rest)) 1 2 3)", "(1 (2 3))"),
+            ("((lambda (a . rest) (list a rest)) 1)", "(1 nil)"),
+            ("((lambda args (length args)) 1 2 3)", "3"),
+            ("((lambda (a (b (* a 2))) (list a b)) 1)", "(1 2)"),
+            ("((lambda (a (b (* a 2))) (list a b)) 1 5)", "(1 5)"),
+            ("((lambda ((a 1) . rest) (list a rest)))", "(1 nil)"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format!("{}", eval_all(&env, &[input])?),
+                expected,
+                "{input}"
+            );
+        }
+        let result = eval_all(
+            &env,
+            &[
+                "(define (log . xs) (apply list 'log xs))",
+                "(define (wrap f) (lambda args (apply f args)))",
+                "((wrap log) 1 2)",
+            ],
+        )?;
+        assert_eq!(format!("{result}"), "(log 1 2)");
+        let result = eval_all(
+            &env,
+            &[
+                "(define area (case-lambda ((r) (* 3 r r)) ((w h) (* w h)) ((w h . more) (length more))))",
+                "(list (area 2) (area 2 3) (area 1 2 3 4))",
+            ],
+        )?;
+        assert_eq!(format!("{result}"), "(12 6 2)");
+        for input in [
+            "(area)",
+            "((lambda (a (b 1)) a))",
+            "((lambda (a (b 1)) a) 1 2 3)",
+        ] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn body_test() -> anyhow::Result<()> {
+        let output = Output::default();
+        let env = GlobalEnvironment::with_output(output.clone());
+        let cases = [
+            ("((lambda (x) x) 1)", "1"),
+            ("((lambda () \"a\"))", "\"a\""),
+            ("((lambda (x) (display x) (newline) (* x 2)) 2)", "4"),
+            ("(let ((x 1) (y 2)) (display x) (+ x y))", "3"),
+            (
+                "(let loop ((i 0)) (display i) (if (< i 3) (loop (+ i 1)) i))",
+                "3",
+            ),
+            (
+                "(do ((i 0 (+ i 1)) (n 5)) ((= i 3) (display i) (+ i n)))",
+                "8",
+            ),
+            ("(do ((i 0 (+ i 1))) ((= i 3)))", "nil"),
+            ("(begin 1 2 3)", "3"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format!("{}", eval_all(&env, &[input])?),
+                expected,
+                "{input}"
+            );
+        }
+        let result = eval_all(
+            &env,
+            &[
+                "(define (f x) (display x) x)",
+                "(define (g) 42)",
+                "(+ (f 1) (g))",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(43)));
+        assert_eq!(output.text(), "2\n1012331");
+        for input in ["(lambda (x))", "(define (f))"] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mutation_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let result = eval_all(
+            &env,
+            &[
+                "(define (make-counter) (define count 0) (lambda () (set! count (+ count 1)) count))",
+                "(define counter (make-counter))",
+                "(counter)",
+                "(counter)",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(2)));
+        assert!(eval_all(&env, &["count"]).is_err(), "count leaked");
+        let result = eval_all(
+            &env,
+            &[
+                "(define x 1)",
+                "(define (f) (define (even? n) (if (= n 0) #t (odd? (- n 1)))) (define (odd? n) (if (= n 0) #f (even? (- n 1)))) (even? 10))",
+                "(let ((x 5)) (set! x 6) x)",
+            ],
+        )?;
+        assert_eq!(result, Value::Number(Number::from(6)));
+        assert_eq!(eval_all(&env, &["(f)"])?, Value::Boolean(true));
+        assert_eq!(
+            eval_all(&env, &["(set! x 2)", "x"])?,
+            Value::Number(Number::from(2))
+        );
+        assert!(eval_all(&env, &["even?"]).is_err(), "even? leaked");
+        assert!(eval_all(&env, &["(set! undefined 1)"]).is_err());
+        let result = eval_all(
+            &env,
+            &[
+                "(define b (box 1))",
+                "(set-box! b (+ (unbox b) 1))",
+                "(list (box? b) (box? 1) (unbox b) b)",
+            ],
+        )?;
+        assert_eq!(format!("{result}"), "(true false 2 #&2)");
+        assert!(eval_all(&env, &["(unbox 1)"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn binding_forms_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let cases = [
+            ("(let* ((x 1) (y (+ x 1))) (* x y))", "2"),
+            ("(let ((x 1)) (let* ((x 2) (y x)) y))", "2"),
+            (
+                "(letrec ((even? (lambda (n) (if (= n 0) #t (odd? (- n 1))))) (odd? (lambda (n) (if (= n 0) #f (even? (- n 1)))))) (even? 11))",
+                "false",
+            ),
+            ("(letrec* ((a 1) (b (+ a 1))) (list a b))", "(1 2)"),
+            (
+                "(let-values (((q r) (values 7 2)) ((all) (values 1)) (rest (values 1 2))) (list q r all rest))",
+                "(7 2 1 (1 2))",
+            ),
+            ("(call-with-values (lambda () (values 1 2)) +)", "3"),
+            ("(define (two) (if #t (values 1 2) 0))", "nil"),
+            ("(call-with-values two list)", "(1 2)"),
+            ("(+ (values 5) 1)", "6"),
+            (
+                "(define fs (let loop ((i 0) (acc nil)) (if (= i 3) acc (loop (+ i 1) (cons (lambda () (loop 3 i)) acc)))))",
+                "nil",
+            ),
+            ("((car fs))", "2"),
+            ("(let loop ((loop 1)) loop)", "1"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format!("{}", eval_all(&env, &[input])?),
+                expected,
+                "{input}"
+            );
+        }
+        for input in [
+            "(let-values (((a b) (values 1 2 3))) a)",
+            "(let* ((x 1)))",
+            "(let ((f (lambda () x)) (x 1)) (f))",
+            "(letrec ((a b) (b 1)) a)",
+            "(letrec* ((a (f)) (f (lambda () 1))) a)",
+            // multiple values are only unpacked by let-values and call-with-values
+            "(define x (values 1 2))",
+            "(list (values))",
+            "(+ (values 1 2) 3)",
+            "(map (lambda (x) (two)) (list 1))",
+        ] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn conditional_test() -> anyhow::Result<()> {
+        let output = Output::default();
+        let env = GlobalEnvironment::with_output(output.clone());
+        let cases = [
+            ("(cond ((= 1 2) 'a))", "nil"),
+            ("(cond ((= 1 1) (display 1) 'a) (else 'b))", "a"),
+            ("(cond ((+ 1 1) => (lambda (x) (* x 10))))", "20"),
+            (
+                "(cond ((null? '(1)) 'empty) ((cdr '(1 2)) => car) (else 'none))",
+                "2",
+            ),
+            ("(cond (#f 1) (3))", "3"),
+            ("(if #f 1)", "nil"),
+            ("(if #t 1)", "1"),
+            ("(when (< 1 2) (display 1) 'yes)", "yes"),
+            ("(when (> 1 2) 'yes)", "nil"),
+            ("(unless (> 1 2) 'no)", "no"),
+            (
+                "(case (* 2 3) ((2 3 5 7) 'prime) ((1 4 6 8 9) 'composite))",
+                "composite",
+            ),
+            ("(case 'x ((a) 1) ((x y) 2) (else 3))", "2"),
+            ("(case \"z\" ((a) 1) (else 3))", "3"),
+            ("(case 5 ((1) 'one))", "nil"),
+            ("(case 5 ((5) => (lambda (x) (+ x 1))))", "6"),
+            // keys are compared as by `eqv?`
+            ("(case \"a\" ((\"a\") 1) (else 2))", "2"),
+            ("(case 2.0 ((2) 'exact) ((2.0) 'inexact))", "inexact"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format!("{}", eval_all(&env, &[input])?),
+                expected,
+                "{input}"
+            );
+        }
+        assert_eq!(output.text(), "11");
+        for input in [
+            "(cond (else 1) (#t 2))",
+            "(cond 1)",
+            "(when #t)",
+            "(case 1 (1 2))",
+        ] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        for (input, message) in [
+            (
+                "(cond (else 1) (#t 2))",
+                "else must be the last cond clause",
+            ),
+            (
+                "(case 1 (else 1) ((1) 2))",
+                "else must be the last case clause",
+            ),
+            ("(if #t)", "Arity mismatch if: expected 2, got 1"),
+            ("(if #t 1 2 3)", "Arity mismatch if: expected 3, got 4"),
+        ] {
+            let err = eval_all(&env, &[input]).err().map(|err| err.to_string());
+            assert!(err.is_some_and(|err| err.ends_with(message)), "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn numeric_tower_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let cases = [
+            ("(* 99999999999 99999999999)", "9999999999800000000001"),
+            ("(- (* 4611686018427387904 2) 1)", "9223372036854775807"),
+            ("(/ 1 3)", "1/3"),
+            ("(+ 1/3 2/3)", "1"),
+            ("(/ 6 4)", "3/2"),
+            ("(* 1/2 4)", "2"),
+            ("(+ 1/2 0.5)", "1.0"),
+            ("(* 2 1.5)", "3.0"),
+            ("(/ 1.0 0)", "+inf.0"),
+            ("(= 1 1.0 2/2)", "true"),
+            ("(< 1/3 0.34 1)", "true"),
+            ("(> 100000000000000000000 1)", "true"),
+            ("(exact 0.25)", "1/4"),
+            ("(exact 2.0)", "2"),
+            ("(inexact 1/4)", "0.25"),
+            ("(- 5)", "-5"),
+            ("(- 1/2)", "-1/2"),
+            ("(/ 2)", "1/2"),
+            ("(/ 0.5)", "2.0"),
+            (
+                "(list (integer? 2) (integer? 2.0) (integer? 1/2) (integer? \"2\"))",
+                "(true true false false)",
+            ),
+            (
+                "(list (rational? 1/2) (rational? 0.5) (rational? (/ 1.0 0)))",
+                "(true true false)",
+            ),
+            (
+                "(list (exact? 1/2) (exact? 0.5) (inexact? 0.5))",
+                "(true false true)",
+            ),
+            (
+                "(list (number? 1) (number? 'a) (exact-integer? 2.0))",
+                "(true false false)",
+            ),
+            ("(eq? 2 2.0)", "false"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format!("{}", eval_all(&env, &[input])?),
+                expected,
+                "{input}"
+            );
+        }
+        for input in ["(/ 1 0)", "(/ 0)", "(exact (/ 1.0 0))", "(exact? 'a)"] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn math_library_test() -> anyhow::Result<()> {
+        let env = GlobalEnvironment::default();
+        let cases = [
+            (
+                "(list (quotient 17 -5) (remainder 17 -5) (modulo 17 -5))",
+                "(-3 2 -3)",
+            ),
+            ("(modulo -7 2.0)", "1.0"),
+            (
+                "(quotient (expt 10 30) 7)",
+                "142857142857142857142857142857",
+            ),
+            ("(list (abs -5/3) (min 1 2.0) (max 3 1/2))", "(5/3 1.0 3)"),
+            ("(list (gcd 12 -18) (lcm 4 6) (gcd))", "(6 12 0)"),
+            (
+                "(list (expt 2 -2) (expt 2.0 0.5))",
+                "(1/4 1.4142135623730951)",
+            ),
+            (
+                "(list (sqrt 16) (sqrt 9/4) (exp 0) (log 100 10))",
+                "(4 3/2 1.0 2.0)",
+            ),
+            (
+                "(call-with-values (lambda () (exact-integer-sqrt 17)) list)",
+                "(4 1)",
+            ),
+            ("(atan 1 1)", "0.7853981633974483"),
+            (
+                "(list (floor -7/2) (ceiling 7/2) (round 5/2) (round 7/2) (truncate -2.7))",
+                "(-4 4 2 4 -2.0)",
+            ),
+            ("(number->string 255 16)", "\"ff\""),
+            ("(number->string -10/3 2)", "\"-1010/11\""),
+            (
+                "(list (string->number \"ff\" 16) (string->number \"abc\"))",
+                "(255 false)",
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                format!("{}", eval_all(&env, &[input])?),
+                expected,
+                "{input}"
+            );
+        }
+        for input in [
+            "(quotient 1 0)",
+            "(quotient 7.5 2)",
+            "(expt 0 -1)",
+            "(expt 10 2000000000)",
+            "(expt 1/3 -2000000000)",
+            "(exact-integer-sqrt -1)",
+            "(sqrt -4)",
+            "(sqrt -1/4)",
+            "(sqrt -2.0)",
+            "(number->string 1.5 2)",
+            "(number->string 1 7)",
+        ] {
+            assert!(eval_all(&env, &[input]).is_err(), "{input} should fail");
+        }
+        // huge exact powers of 1 and -1 are still computed
+        let result = eval_all(&env, &["(list (expt 1 2000000000) (expt -1 2000000001))"])?;
+        assert_eq!(result.to_string(), "(1 -1)");
+        Ok(())
+    }
+}
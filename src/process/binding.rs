@@ -0,0 +1,186 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::{Environment, Scope},
+    errors::LispComputerError,
+    parse::Expression,
+    value::Value,
+};
+
+use super::{Function, Tail, eval_body, lambda_params};
+
+/// Reads `((name init)...)`, the bindings of the `let` family.
+fn bindings<'a>(
+    name: &str,
+    bindings: &'a [Expression],
+) -> Result<Vec<(&'a str, &'a Expression)>, LispComputerError> {
+    bindings
+        .iter()
+        .map(|binding| match binding {
+            Expression::List(binding, _) => match binding.as_slice() {
+                [Expression::Variable(variable, _), init] => Ok((variable.as_str(), init)),
+                _ => Err(LispComputerError::InvalidArguments(
+                    name.to_string(),
+                    binding.clone(),
+                )),
+            },
+            _ => Err(LispComputerError::InvalidArguments(
+                name.to_string(),
+                bindings.to_vec(),
+            )),
+        })
+        .collect()
+}
+
+/// Splits `(bindings body...)`, the body having at least one expression.
+fn split_let<'a>(
+    name: &str,
+    args: &'a [Expression],
+) -> Result<(&'a [Expression], &'a [Expression]), LispComputerError> {
+    match args {
+        [Expression::List(bindings, _), body @ ..] if !body.is_empty() => Ok((bindings, body)),
+        _ => Err(LispComputerError::InvalidArguments(
+            name.to_string(),
+            args.to_vec(),
+        )),
+    }
+}
+
+/// `(let* ((name init)...) body...)` binds in order, each init seeing the
+/// bindings before it.
+pub struct LetStarProcessor;
+
+impl<T: Environment> Function<T> for LetStarProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let name = <Self as Function<T>>::name(self);
+        let (let_bindings, body) = split_let(name, args)?;
+        let mut let_scope = scope.clone();
+        for (variable, init) in bindings(name, let_bindings)? {
+            let value = init.eval(env, &let_scope)?;
+            let_scope = Scope::new(&let_scope);
+            let_scope.insert(variable.to_string(), value);
+        }
+        eval_body(body, env, &Scope::new(&let_scope))
+    }
+
+    fn name(&self) -> &str {
+        "let*"
+    }
+}
+
+/// Binds the names of `letrec` and `letrec*` before evaluating the inits in their
+/// scope, so the inits may refer to each other. Reading a name before it is assigned
+/// is an error. With `sequential` each value is assigned as soon as its init is
+/// evaluated.
+fn letrec<T: Environment>(
+    name: &str,
+    args: &[Expression],
+    sequential: bool,
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Tail, LispComputerError> {
+    let (let_bindings, body) = split_let(name, args)?;
+    let let_bindings = bindings(name, let_bindings)?;
+    let let_scope = Scope::new(scope);
+    for (variable, _) in &let_bindings {
+        let_scope.insert(variable.to_string(), Value::Unassigned);
+    }
+    let mut values = Vec::new();
+    for (variable, init) in &let_bindings {
+        let value = init.eval(env, &let_scope)?;
+        if sequential {
+            let_scope.insert(variable.to_string(), value);
+        } else {
+            values.push((variable.to_string(), value));
+        }
+    }
+    for (variable, value) in values {
+        let_scope.insert(variable, value);
+    }
+    eval_body(body, env, &let_scope)
+}
+
+pub struct LetrecProcessor;
+
+impl<T: Environment> Function<T> for LetrecProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        letrec(<Self as Function<T>>::name(self), args, false, env, scope)
+    }
+
+    fn name(&self) -> &str {
+        "letrec"
+    }
+}
+
+pub struct LetrecStarProcessor;
+
+impl<T: Environment> Function<T> for LetrecStarProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        letrec(<Self as Function<T>>::name(self), args, true, env, scope)
+    }
+
+    fn name(&self) -> &str {
+        "letrec*"
+    }
+}
+
+/// `(let-values ((formals init)...) body...)` binds the values returned by each
+/// init to its formals, which take the same forms as those of a lambda.
+pub struct LetValuesProcessor;
+
+impl<T: Environment> Function<T> for LetValuesProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let name = <Self as Function<T>>::name(self);
+        let (let_bindings, body) = split_let(name, args)?;
+        let let_scope = Scope::new(scope);
+        for binding in let_bindings {
+            let Expression::List(binding, _) = binding else {
+                return Err(LispComputerError::InvalidArguments(
+                    name.to_string(),
+                    let_bindings.to_vec(),
+                ));
+            };
+            let [formals, init] = binding.as_slice() else {
+                return Err(LispComputerError::InvalidArguments(
+                    name.to_string(),
+                    binding.clone(),
+                ));
+            };
+            let params = lambda_params(formals)?;
+            let values = init.eval_values(env, scope)?.into_values();
+            if !params.accepts(values.len()) {
+                return Err(LispComputerError::ArityMismatch(
+                    name.to_string(),
+                    params.expected(values.len()),
+                    values.len(),
+                ));
+            }
+            params.bind(values, env, &let_scope)?;
+        }
+        eval_body(body, env, &let_scope)
+    }
+
+    fn name(&self) -> &str {
+        "let-values"
+    }
+}
@@ -0,0 +1,71 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{environment::Environment, errors::LispComputerError, value::Value};
+
+use super::{Procedure, check_arity};
+
+fn cell<'a>(name: &str, value: &'a Value) -> Result<&'a RefCell<Value>, LispComputerError> {
+    match value {
+        Value::Box(cell) => Ok(cell),
+        other => Err(LispComputerError::TypeMismatch1 {
+            operation: name.to_string(),
+            left: other.clone(),
+        }),
+    }
+}
+
+pub struct BoxProcessor;
+
+impl<T: Environment> Procedure<T> for BoxProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 1, &args)?;
+        Ok(Value::Box(Rc::new(RefCell::new(args.remove(0)))))
+    }
+
+    fn name(&self) -> &str {
+        "box"
+    }
+}
+
+pub struct IsBoxProcessor;
+
+impl<T: Environment> Procedure<T> for IsBoxProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 1, &args)?;
+        Ok(Value::Boolean(matches!(args[0], Value::Box(_))))
+    }
+
+    fn name(&self) -> &str {
+        "box?"
+    }
+}
+
+pub struct UnboxProcessor;
+
+impl<T: Environment> Procedure<T> for UnboxProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        Ok(cell(name, &args[0])?.borrow().clone())
+    }
+
+    fn name(&self) -> &str {
+        "unbox"
+    }
+}
+
+pub struct SetBoxProcessor;
+
+impl<T: Environment> Procedure<T> for SetBoxProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 2, &args)?;
+        let value = args.remove(1);
+        cell(name, &args[0])?.replace(value);
+        Ok(Value::Nil)
+    }
+
+    fn name(&self) -> &str {
+        "set-box!"
+    }
+}
@@ -0,0 +1,145 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::{Environment, Scope},
+    errors::LispComputerError,
+    parse::Expression,
+    value::Value,
+};
+
+use super::{Function, Tail, eval_body, tail_call_procedure};
+
+/// Evaluates the body of `when` and `unless` if the test is `expected`.
+fn conditional_body<T: Environment>(
+    name: &str,
+    args: &[Expression],
+    expected: bool,
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Tail, LispComputerError> {
+    let [test, body @ ..] = args else {
+        return Err(LispComputerError::InvalidArguments(
+            name.to_string(),
+            args.to_vec(),
+        ));
+    };
+    if body.is_empty() {
+        return Err(LispComputerError::InvalidArguments(
+            name.to_string(),
+            args.to_vec(),
+        ));
+    }
+    if test.eval(env, scope)?.boolean() == expected {
+        eval_body(body, env, scope)
+    } else {
+        Ok(Tail::Value(Value::Nil))
+    }
+}
+
+pub struct WhenProcessor;
+
+impl<T: Environment> Function<T> for WhenProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        conditional_body(<Self as Function<T>>::name(self), args, true, env, scope)
+    }
+
+    fn name(&self) -> &str {
+        "when"
+    }
+}
+
+pub struct UnlessProcessor;
+
+impl<T: Environment> Function<T> for UnlessProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        conditional_body(<Self as Function<T>>::name(self), args, false, env, scope)
+    }
+
+    fn name(&self) -> &str {
+        "unless"
+    }
+}
+
+/// `(case key ((datum...) expr...)... (else expr...))` evaluates the first clause
+/// listing a datum `eqv?` to the key. A clause may also be `((datum...) => receiver)`
+/// to pass the key to `receiver`.
+pub struct CaseProcessor;
+
+impl<T: Environment> Function<T> for CaseProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let name = <Self as Function<T>>::name(self);
+        let [key, clauses @ ..] = args else {
+            return Err(LispComputerError::InvalidArguments(
+                name.to_string(),
+                args.to_vec(),
+            ));
+        };
+        let key = key.eval(env, scope)?;
+        for (index, clause) in clauses.iter().enumerate() {
+            let (matches, body) = match clause {
+                Expression::List(clause, _) => match clause.as_slice() {
+                    [Expression::Variable(else_, _), body @ ..] if else_ == "else" => {
+                        if index != clauses.len() - 1 {
+                            return Err(LispComputerError::MisplacedElse(name.to_string()));
+                        }
+                        (true, body)
+                    }
+                    [Expression::List(data, _), body @ ..] => {
+                        let mut matches = false;
+                        for datum in data {
+                            matches |= datum.to_datum()?.is_eqv(&key);
+                        }
+                        (matches, body)
+                    }
+                    _ => {
+                        return Err(LispComputerError::InvalidArguments(
+                            name.to_string(),
+                            clause.clone(),
+                        ));
+                    }
+                },
+                _ => {
+                    return Err(LispComputerError::InvalidArguments(
+                        name.to_string(),
+                        args.to_vec(),
+                    ));
+                }
+            };
+            if !matches {
+                continue;
+            }
+            return match body {
+                [] => Err(LispComputerError::InvalidArguments(
+                    name.to_string(),
+                    vec![clause.clone()],
+                )),
+                [Expression::Variable(arrow, _), receiver] if arrow == "=>" => {
+                    let receiver = receiver.eval(env, scope)?;
+                    tail_call_procedure(&receiver, vec![key], env)
+                }
+                body => eval_body(body, env, scope),
+            };
+        }
+        // without a matching clause the value is unspecified
+        Ok(Tail::Value(Value::Nil))
+    }
+
+    fn name(&self) -> &str {
+        "case"
+    }
+}
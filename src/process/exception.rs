@@ -0,0 +1,236 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::{Environment, Scope},
+    errors::LispComputerError,
+    parse::Expression,
+    value::{ErrorObject, Value},
+};
+
+use super::{
+    Function, Procedure, Tail, apply_procedure, check_arity, eval_clauses, tail_call_procedure,
+};
+
+/// An entry of the handler stack. A `guard` catches raised values by unwinding to
+/// it, so handlers installed outside of it are not called.
+pub enum Handler {
+    Procedure(Value),
+    Guard,
+}
+
+/// Passes `value` to the innermost handler, which runs with the outer handlers
+/// installed. The handler's result is returned for `raise-continuable`, after any
+/// other raise the value keeps unwinding to the enclosing `guard`.
+fn raise<T: Environment>(
+    value: Value,
+    continuable: bool,
+    env: &T,
+) -> Result<Value, LispComputerError> {
+    let Some(handler) = env.pop_handler() else {
+        return Err(LispComputerError::Raised(value));
+    };
+    let result = match &handler {
+        Handler::Procedure(procedure) => apply_procedure(procedure, vec![value.clone()], env),
+        Handler::Guard => Err(LispComputerError::Raised(value.clone())),
+    };
+    env.push_handler(handler);
+    match result {
+        Ok(result) if continuable => Ok(result),
+        Ok(_) => Err(LispComputerError::Raised(value)),
+        Err(err) => Err(err),
+    }
+}
+
+fn error_object<'a>(name: &str, value: &'a Value) -> Result<&'a ErrorObject, LispComputerError> {
+    match value {
+        Value::Error(error) => Ok(error),
+        other => Err(LispComputerError::TypeMismatch1 {
+            operation: name.to_string(),
+            left: other.clone(),
+        }),
+    }
+}
+
+pub struct RaiseProcessor;
+
+impl<T: Environment> Procedure<T> for RaiseProcessor {
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 1, &args)?;
+        raise(args.remove(0), false, env)
+    }
+
+    fn name(&self) -> &str {
+        "raise"
+    }
+}
+
+pub struct RaiseContinuableProcessor;
+
+impl<T: Environment> Procedure<T> for RaiseContinuableProcessor {
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 1, &args)?;
+        raise(args.remove(0), true, env)
+    }
+
+    fn name(&self) -> &str {
+        "raise-continuable"
+    }
+}
+
+pub struct ErrorProcessor;
+
+impl<T: Environment> Procedure<T> for ErrorProcessor {
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let mut args = args.into_iter();
+        let message = match args.next() {
+            Some(Value::String(message)) => message,
+            Some(other) => {
+                return Err(LispComputerError::TypeMismatch1 {
+                    operation: name.to_string(),
+                    left: other,
+                });
+            }
+            None => return Err(LispComputerError::ArityMismatch(name.to_string(), 1, 0)),
+        };
+        let error = ErrorObject {
+            message,
+            irritants: args.collect(),
+        };
+        raise(Value::Error(Rc::new(error)), false, env)
+    }
+
+    fn name(&self) -> &str {
+        "error"
+    }
+}
+
+pub struct ErrorObjectProcessor;
+
+impl<T: Environment> Procedure<T> for ErrorObjectProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 1, &args)?;
+        Ok(Value::Boolean(matches!(args.remove(0), Value::Error(_))))
+    }
+
+    fn name(&self) -> &str {
+        "error-object?"
+    }
+}
+
+pub struct ErrorObjectMessageProcessor;
+
+impl<T: Environment> Procedure<T> for ErrorObjectMessageProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let error = error_object(name, &args[0])?;
+        Ok(Value::String(error.message.to_string()))
+    }
+
+    fn name(&self) -> &str {
+        "error-object-message"
+    }
+}
+
+pub struct ErrorObjectIrritantsProcessor;
+
+impl<T: Environment> Procedure<T> for ErrorObjectIrritantsProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let error = error_object(name, &args[0])?;
+        Ok(Value::list(error.irritants.clone()))
+    }
+
+    fn name(&self) -> &str {
+        "error-object-irritants"
+    }
+}
+
+pub struct WithExceptionHandlerProcessor;
+
+impl<T: Environment> Procedure<T> for WithExceptionHandlerProcessor {
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 2, &args)?;
+        let thunk = args.remove(1);
+        let handler = args.remove(0);
+        env.push_handler(Handler::Procedure(handler.clone()));
+        // the thunk returns its results as they are, several for `values`
+        let result = tail_call_procedure(&thunk, Vec::new(), env).and_then(|tail| tail.eval(env));
+        env.pop_handler();
+        match result {
+            // raised values already went through the handler, built-in errors are
+            // shown to it as they unwind
+            Err(err) if err.raised().is_none() => {
+                apply_procedure(&handler, vec![err.condition()], env)?;
+                Err(err)
+            }
+            result => result,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "with-exception-handler"
+    }
+}
+
+/// `(guard (var clause...) body...)` evaluates the body, and on an error binds its
+/// condition to `var` and evaluates the first matching `cond` clause. Without a
+/// matching clause the condition is raised again.
+pub struct GuardProcessor;
+
+impl<T: Environment> Function<T> for GuardProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let (spec, body) = match args {
+            [Expression::List(spec, _), body @ ..] if !body.is_empty() => (spec, body),
+            _ => {
+                return Err(LispComputerError::InvalidArguments(
+                    <Self as Function<T>>::name(self).to_string(),
+                    args.to_vec(),
+                ));
+            }
+        };
+        let [Expression::Variable(var, _), clauses @ ..] = spec.as_slice() else {
+            return Err(LispComputerError::InvalidArguments(
+                <Self as Function<T>>::name(self).to_string(),
+                spec.clone(),
+            ));
+        };
+        env.push_handler(Handler::Guard);
+        // the body is not in tail position, its errors must be caught here, and its
+        // internal defines stay local to it
+        let body_scope = Scope::new(scope);
+        let result = body.iter().try_fold(Value::Nil, |_, expression| {
+            expression.eval_values(env, &body_scope)
+        });
+        env.pop_handler();
+        let err = match result {
+            Ok(value) => return Ok(Tail::Value(value)),
+            Err(err) => err,
+        };
+        let guard_scope = Scope::new(scope);
+        guard_scope.insert(var.to_string(), err.condition());
+        match eval_clauses(clauses, env, &guard_scope)? {
+            Some(tail) => Ok(tail),
+            None => match err.raised() {
+                // unless an outer handler returns a value, the original error with
+                // its location keeps unwinding
+                Some(value) => match raise(value.clone(), true, env) {
+                    Err(LispComputerError::Raised(_)) => Err(err),
+                    result => result.map(Tail::Value),
+                },
+                None => Err(err),
+            },
+        }
+    }
+
+    fn name(&self) -> &str {
+        "guard"
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{environment::Environment, errors::LispComputerError, value::Value};
+
+use super::{Procedure, Tail, apply_procedure, check_arity, list::list_items, tail_call_procedure};
+
+/// Checks that a procedure received at least `expected` arguments.
+fn check_min_arity(name: &str, expected: usize, args: &[Value]) -> Result<(), LispComputerError> {
+    if args.len() < expected {
+        return Err(LispComputerError::ArityMismatch(
+            name.to_string(),
+            expected,
+            args.len(),
+        ));
+    }
+    Ok(())
+}
+
+/// Turns the list arguments into rows of elements, one row per position, stopping at
+/// the end of the shortest list.
+fn transpose(name: &str, lists: Vec<Value>) -> Result<Vec<Vec<Value>>, LispComputerError> {
+    let lists = lists
+        .into_iter()
+        .map(|list| list_items(name, list))
+        .collect::<Result<Vec<_>, _>>()?;
+    let len = lists.iter().map(Vec::len).min().unwrap_or(0);
+    Ok((0..len)
+        .map(|index| lists.iter().map(|list| list[index].clone()).collect())
+        .collect())
+}
+
+pub struct MapProcessor;
+
+impl<T: Environment> Procedure<T> for MapProcessor {
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_min_arity(name, 2, &args)?;
+        let procedure = args.remove(0);
+        let values = transpose(name, args)?
+            .into_iter()
+            .map(|row| apply_procedure(&procedure, row, env))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::list(values))
+    }
+
+    fn name(&self) -> &str {
+        "map"
+    }
+}
+
+pub struct ForEachProcessor;
+
+impl<T: Environment> Procedure<T> for ForEachProcessor {
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_min_arity(name, 2, &args)?;
+        let procedure = args.remove(0);
+        for row in transpose(name, args)? {
+            apply_procedure(&procedure, row, env)?;
+        }
+        Ok(Value::Nil)
+    }
+
+    fn name(&self) -> &str {
+        "for-each"
+    }
+}
+
+pub struct FilterProcessor;
+
+impl<T: Environment> Procedure<T> for FilterProcessor {
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let [predicate, list] = <[Value; 2]>::try_from(args)
+            .map_err(|args| LispComputerError::ArityMismatch(name.to_string(), 2, args.len()))?;
+        let mut values = Vec::new();
+        for item in list_items(name, list)? {
+            if apply_procedure(&predicate, vec![item.clone()], env)?.boolean() {
+                values.push(item);
+            }
+        }
+        Ok(Value::list(values))
+    }
+
+    fn name(&self) -> &str {
+        "filter"
+    }
+}
+
+pub struct ReduceProcessor;
+
+impl<T: Environment> Procedure<T> for ReduceProcessor {
+    /// `(reduce f initial list)` folds `(f item acc)` over the list, starting from its
+    /// first element; `initial` is only returned for the empty list.
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let [procedure, initial, list] = <[Value; 3]>::try_from(args)
+            .map_err(|args| LispComputerError::ArityMismatch(name.to_string(), 3, args.len()))?;
+        let mut items = list_items(name, list)?.into_iter();
+        let Some(first) = items.next() else {
+            return Ok(initial);
+        };
+        items.try_fold(first, |acc, item| {
+            apply_procedure(&procedure, vec![item, acc], env)
+        })
+    }
+
+    fn name(&self) -> &str {
+        "reduce"
+    }
+}
+
+pub struct FoldLeftProcessor;
+
+impl<T: Environment> Procedure<T> for FoldLeftProcessor {
+    /// `(fold-left f initial list ...)` calls `(f acc item ...)` from left to right.
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_min_arity(name, 3, &args)?;
+        let procedure = args.remove(0);
+        let initial = args.remove(0);
+        transpose(name, args)?
+            .into_iter()
+            .try_fold(initial, |acc, mut row| {
+                row.insert(0, acc);
+                apply_procedure(&procedure, row, env)
+            })
+    }
+
+    fn name(&self) -> &str {
+        "fold-left"
+    }
+}
+
+pub struct FoldRightProcessor;
+
+impl<T: Environment> Procedure<T> for FoldRightProcessor {
+    /// `(fold-right f initial list ...)` calls `(f item ... acc)` from right to left.
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_min_arity(name, 3, &args)?;
+        let procedure = args.remove(0);
+        let initial = args.remove(0);
+        transpose(name, args)?
+            .into_iter()
+            .rev()
+            .try_fold(initial, |acc, mut row| {
+                row.push(acc);
+                apply_procedure(&procedure, row, env)
+            })
+    }
+
+    fn name(&self) -> &str {
+        "fold-right"
+    }
+}
+
+pub struct ApplyProcessor;
+
+impl<T: Environment> Procedure<T> for ApplyProcessor {
+    /// `(apply f a b list)` calls `f` with `a`, `b` and the elements of `list`.
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        <Self as Procedure<T>>::tail_call(self, args, env)?.eval(env)
+    }
+
+    fn tail_call(&self, mut args: Vec<Value>, env: &T) -> Result<Tail, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_min_arity(name, 2, &args)?;
+        let procedure = args.remove(0);
+        let spread = args.pop().unwrap_or(Value::Nil);
+        args.extend(list_items(name, spread)?);
+        tail_call_procedure(&procedure, args, env)
+    }
+
+    fn name(&self) -> &str {
+        "apply"
+    }
+}
+
+pub struct ValuesProcessor;
+
+impl<T: Environment> Procedure<T> for ValuesProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        match args.len() {
+            1 => Ok(args.remove(0)),
+            _ => Ok(Value::Values(args)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "values"
+    }
+}
+
+pub struct CallWithValuesProcessor;
+
+impl<T: Environment> Procedure<T> for CallWithValuesProcessor {
+    /// `(call-with-values producer consumer)` calls `consumer` with the values
+    /// returned by `producer`.
+    fn call(&self, mut args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        check_arity(<Self as Procedure<T>>::name(self), 2, &args)?;
+        let consumer = args.remove(1);
+        let values = tail_call_procedure(&args[0], Vec::new(), env)?
+            .eval(env)?
+            .into_values();
+        apply_procedure(&consumer, values, env)
+    }
+
+    fn name(&self) -> &str {
+        "call-with-values"
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{environment::Environment, errors::LispComputerError, value::Value};
+
+use super::{Procedure, check_arity};
+
+pub struct DisplayProcessor;
+
+impl<T: Environment> Procedure<T> for DisplayProcessor {
+    /// Writes a value to the output of the environment, strings without their
+    /// quotes.
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let mut output = env.output();
+        match &args[0] {
+            Value::String(string) => write!(output, "{string}")?,
+            value => write!(output, "{value}")?,
+        }
+        output.flush()?;
+        Ok(Value::Nil)
+    }
+
+    fn name(&self) -> &str {
+        "display"
+    }
+}
+
+pub struct NewlineProcessor;
+
+impl<T: Environment> Procedure<T> for NewlineProcessor {
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 0, &args)?;
+        let mut output = env.output();
+        writeln!(output)?;
+        output.flush()?;
+        Ok(Value::Nil)
+    }
+
+    fn name(&self) -> &str {
+        "newline"
+    }
+}
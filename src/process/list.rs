@@ -0,0 +1,244 @@
+use crate::{
+    environment::Environment,
+    errors::LispComputerError,
+    value::{Number, Value},
+};
+
+use super::{Procedure, check_arity};
+
+pub(super) fn list_items(name: &str, value: Value) -> Result<Vec<Value>, LispComputerError> {
+    match value.list_items() {
+        Some(items) => Ok(items),
+        None => Err(LispComputerError::TypeMismatch1 {
+            operation: name.to_string(),
+            left: value,
+        }),
+    }
+}
+
+fn index(name: &str, value: Value) -> Result<usize, LispComputerError> {
+    let index = match &value {
+        Value::Number(n) => n.to_usize(),
+        _ => None,
+    };
+    index.ok_or(LispComputerError::TypeMismatch1 {
+        operation: name.to_string(),
+        left: value,
+    })
+}
+
+/// Drops the first `k` pairs of a list.
+fn list_tail(name: &str, list: Value, k: usize) -> Result<Value, LispComputerError> {
+    let mut tail = list;
+    for _ in 0..k {
+        tail = match tail {
+            Value::Pair(pair) => pair.cdr.clone(),
+            _ => return Err(LispComputerError::IndexOutOfRange(name.to_string(), k)),
+        };
+    }
+    Ok(tail)
+}
+
+pub struct ConsProcessor;
+
+impl<T: Environment> Procedure<T> for ConsProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 2, &args)?;
+        let cdr = args.pop().unwrap_or(Value::Nil);
+        let car = args.pop().unwrap_or(Value::Nil);
+        Ok(Value::cons(car, cdr))
+    }
+
+    fn name(&self) -> &str {
+        "cons"
+    }
+}
+
+pub struct CarProcessor;
+
+impl<T: Environment> Procedure<T> for CarProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        match args.remove(0) {
+            Value::Pair(pair) => Ok(pair.car.clone()),
+            other => Err(LispComputerError::TypeMismatch1 {
+                operation: name.to_string(),
+                left: other,
+            }),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "car"
+    }
+}
+
+pub struct CdrProcessor;
+
+impl<T: Environment> Procedure<T> for CdrProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        match args.remove(0) {
+            Value::Pair(pair) => Ok(pair.cdr.clone()),
+            other => Err(LispComputerError::TypeMismatch1 {
+                operation: name.to_string(),
+                left: other,
+            }),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "cdr"
+    }
+}
+
+pub struct ListProcessor;
+
+impl<T: Environment> Procedure<T> for ListProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        Ok(Value::list(args))
+    }
+
+    fn name(&self) -> &str {
+        "list"
+    }
+}
+
+pub struct NullProcessor;
+
+impl<T: Environment> Procedure<T> for NullProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let value = args.remove(0);
+        Ok(Value::Boolean(value == Value::Nil))
+    }
+
+    fn name(&self) -> &str {
+        "null?"
+    }
+}
+
+pub struct PairProcessor;
+
+impl<T: Environment> Procedure<T> for PairProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let value = args.remove(0);
+        Ok(Value::Boolean(matches!(value, Value::Pair(_))))
+    }
+
+    fn name(&self) -> &str {
+        "pair?"
+    }
+}
+
+pub struct IsListProcessor;
+
+impl<T: Environment> Procedure<T> for IsListProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let value = args.remove(0);
+        Ok(Value::Boolean(value.list_items().is_some()))
+    }
+
+    fn name(&self) -> &str {
+        "list?"
+    }
+}
+
+pub struct LengthProcessor;
+
+impl<T: Environment> Procedure<T> for LengthProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let list = args.remove(0);
+        let items = list_items(name, list)?;
+        Ok(Value::Number(Number::from(items.len() as i64)))
+    }
+
+    fn name(&self) -> &str {
+        "length"
+    }
+}
+
+pub struct AppendProcessor;
+
+impl<T: Environment> Procedure<T> for AppendProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let Some(tail) = args.pop() else {
+            return Ok(Value::Nil);
+        };
+        let mut items = Vec::new();
+        for list in args {
+            items.extend(list_items(name, list)?);
+        }
+        // the last argument is shared, not copied, and may be any value
+        Ok(items
+            .into_iter()
+            .rev()
+            .fold(tail, |tail, item| Value::cons(item, tail)))
+    }
+
+    fn name(&self) -> &str {
+        "append"
+    }
+}
+
+pub struct ReverseProcessor;
+
+impl<T: Environment> Procedure<T> for ReverseProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let list = args.remove(0);
+        let items = list_items(name, list)?;
+        Ok(items
+            .into_iter()
+            .fold(Value::Nil, |tail, item| Value::cons(item, tail)))
+    }
+
+    fn name(&self) -> &str {
+        "reverse"
+    }
+}
+
+pub struct ListTailProcessor;
+
+impl<T: Environment> Procedure<T> for ListTailProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 2, &args)?;
+        let k = index(name, args.remove(1))?;
+        list_tail(name, args.remove(0), k)
+    }
+
+    fn name(&self) -> &str {
+        "list-tail"
+    }
+}
+
+pub struct ListRefProcessor;
+
+impl<T: Environment> Procedure<T> for ListRefProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 2, &args)?;
+        let k = index(name, args.remove(1))?;
+        match list_tail(name, args.remove(0), k)? {
+            Value::Pair(pair) => Ok(pair.car.clone()),
+            _ => Err(LispComputerError::IndexOutOfRange(name.to_string(), k)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "list-ref"
+    }
+}
@@ -0,0 +1,606 @@
+use std::cmp::Ordering;
+
+use crate::{
+    environment::Environment,
+    errors::LispComputerError,
+    value::{Number, Value},
+};
+
+use super::{Procedure, check_arity};
+
+/// The single numeric argument of a procedure.
+fn number_arg<'a>(name: &str, args: &'a [Value]) -> Result<&'a Number, LispComputerError> {
+    check_arity(name, 1, args)?;
+    match &args[0] {
+        Value::Number(number) => Ok(number),
+        other => Err(LispComputerError::TypeMismatch1 {
+            operation: name.to_string(),
+            left: other.clone(),
+        }),
+    }
+}
+
+fn type_mismatch(name: &str, value: &Value) -> LispComputerError {
+    LispComputerError::TypeMismatch1 {
+        operation: name.to_string(),
+        left: value.clone(),
+    }
+}
+
+/// Checks that a procedure received between `min` and `max` arguments.
+fn check_arity_range(
+    name: &str,
+    min: usize,
+    max: usize,
+    args: &[Value],
+) -> Result<(), LispComputerError> {
+    if args.len() < min || args.len() > max {
+        let expected = if args.len() < min { min } else { max };
+        return Err(LispComputerError::ArityMismatch(
+            name.to_string(),
+            expected,
+            args.len(),
+        ));
+    }
+    Ok(())
+}
+
+/// The numeric arguments of a procedure.
+fn numbers<'a>(name: &str, args: &'a [Value]) -> Result<Vec<&'a Number>, LispComputerError> {
+    args.iter()
+        .map(|arg| match arg {
+            Value::Number(number) => Ok(number),
+            other => Err(type_mismatch(name, other)),
+        })
+        .collect()
+}
+
+/// The integer arguments of a procedure, exact or inexact.
+fn integers<'a>(name: &str, args: &'a [Value]) -> Result<Vec<&'a Number>, LispComputerError> {
+    args.iter()
+        .map(|arg| match arg {
+            Value::Number(number) if number.is_integer() => Ok(number),
+            other => Err(type_mismatch(name, other)),
+        })
+        .collect()
+}
+
+/// The radix argument of the conversions between numbers and strings, 10 when
+/// omitted.
+fn radix(name: &str, arg: Option<&Value>) -> Result<u32, LispComputerError> {
+    match arg {
+        None => Ok(10),
+        Some(Value::Number(Number::Integer(radix @ (2 | 8 | 10 | 16)))) => Ok(*radix as u32),
+        Some(other) => Err(type_mismatch(name, other)),
+    }
+}
+
+/// Divides two integers, failing on a division by zero.
+fn integer_division(
+    name: &str,
+    args: &[Value],
+    division: fn(&Number, &Number) -> Option<Number>,
+) -> Result<Value, LispComputerError> {
+    check_arity(name, 2, args)?;
+    let integers = integers(name, args)?;
+    division(integers[0], integers[1])
+        .map(Value::Number)
+        .ok_or_else(|| LispComputerError::DivisionByZero(name.to_string()))
+}
+
+/// The smallest or the largest argument, inexact if any argument is.
+fn extremum(name: &str, args: &[Value], keep: Ordering) -> Result<Value, LispComputerError> {
+    let numbers = numbers(name, args)?;
+    let Some((first, rest)) = numbers.split_first() else {
+        return Err(LispComputerError::ArityMismatch(name.to_string(), 1, 0));
+    };
+    let result = rest.iter().fold(*first, |result, number| {
+        if number.compare(result) == Some(keep) {
+            number
+        } else {
+            result
+        }
+    });
+    Ok(Value::Number(if numbers.iter().all(|n| n.is_exact()) {
+        result.clone()
+    } else {
+        result.to_inexact()
+    }))
+}
+
+/// Applies an inexact function of one number.
+fn real_function(
+    name: &str,
+    args: &[Value],
+    function: fn(f64) -> f64,
+) -> Result<Value, LispComputerError> {
+    let number = number_arg(name, args)?;
+    Ok(Value::Number(Number::from(function(number.to_f64()))))
+}
+
+/// A type predicate, false for values that are not numbers.
+fn type_predicate(
+    name: &str,
+    args: &[Value],
+    predicate: fn(&Number) -> bool,
+) -> Result<Value, LispComputerError> {
+    check_arity(name, 1, args)?;
+    Ok(Value::Boolean(match &args[0] {
+        Value::Number(number) => predicate(number),
+        _ => false,
+    }))
+}
+
+pub struct IsNumberProcessor;
+
+impl<T: Environment> Procedure<T> for IsNumberProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        type_predicate(<Self as Procedure<T>>::name(self), &args, |_| true)
+    }
+
+    fn name(&self) -> &str {
+        "number?"
+    }
+}
+
+pub struct IsRealProcessor;
+
+impl<T: Environment> Procedure<T> for IsRealProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        type_predicate(<Self as Procedure<T>>::name(self), &args, |_| true)
+    }
+
+    fn name(&self) -> &str {
+        "real?"
+    }
+}
+
+pub struct IsRationalProcessor;
+
+impl<T: Environment> Procedure<T> for IsRationalProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        type_predicate(
+            <Self as Procedure<T>>::name(self),
+            &args,
+            Number::is_rational,
+        )
+    }
+
+    fn name(&self) -> &str {
+        "rational?"
+    }
+}
+
+pub struct IsIntegerProcessor;
+
+impl<T: Environment> Procedure<T> for IsIntegerProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        type_predicate(
+            <Self as Procedure<T>>::name(self),
+            &args,
+            Number::is_integer,
+        )
+    }
+
+    fn name(&self) -> &str {
+        "integer?"
+    }
+}
+
+pub struct IsExactIntegerProcessor;
+
+impl<T: Environment> Procedure<T> for IsExactIntegerProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        type_predicate(<Self as Procedure<T>>::name(self), &args, |number| {
+            number.is_exact() && number.is_integer()
+        })
+    }
+
+    fn name(&self) -> &str {
+        "exact-integer?"
+    }
+}
+
+pub struct IsExactProcessor;
+
+impl<T: Environment> Procedure<T> for IsExactProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Boolean(number.is_exact()))
+    }
+
+    fn name(&self) -> &str {
+        "exact?"
+    }
+}
+
+pub struct IsInexactProcessor;
+
+impl<T: Environment> Procedure<T> for IsInexactProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Boolean(!number.is_exact()))
+    }
+
+    fn name(&self) -> &str {
+        "inexact?"
+    }
+}
+
+pub struct ExactProcessor;
+
+impl<T: Environment> Procedure<T> for ExactProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let number = number_arg(name, &args)?;
+        match number.to_exact() {
+            Some(exact) => Ok(Value::Number(exact)),
+            // infinities and NaN have no exact counterpart
+            None => Err(LispComputerError::TypeMismatch1 {
+                operation: name.to_string(),
+                left: args[0].clone(),
+            }),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "exact"
+    }
+}
+
+pub struct InexactProcessor;
+
+impl<T: Environment> Procedure<T> for InexactProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Number(number.to_inexact()))
+    }
+
+    fn name(&self) -> &str {
+        "inexact"
+    }
+}
+
+pub struct QuotientProcessor;
+
+impl<T: Environment> Procedure<T> for QuotientProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        integer_division(<Self as Procedure<T>>::name(self), &args, Number::quotient)
+    }
+
+    fn name(&self) -> &str {
+        "quotient"
+    }
+}
+
+pub struct RemainderProcessor;
+
+impl<T: Environment> Procedure<T> for RemainderProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        integer_division(<Self as Procedure<T>>::name(self), &args, Number::remainder)
+    }
+
+    fn name(&self) -> &str {
+        "remainder"
+    }
+}
+
+pub struct ModuloProcessor;
+
+impl<T: Environment> Procedure<T> for ModuloProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        integer_division(<Self as Procedure<T>>::name(self), &args, Number::modulo)
+    }
+
+    fn name(&self) -> &str {
+        "modulo"
+    }
+}
+
+pub struct AbsProcessor;
+
+impl<T: Environment> Procedure<T> for AbsProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Number(number.abs()))
+    }
+
+    fn name(&self) -> &str {
+        "abs"
+    }
+}
+
+pub struct MinProcessor;
+
+impl<T: Environment> Procedure<T> for MinProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        extremum(<Self as Procedure<T>>::name(self), &args, Ordering::Less)
+    }
+
+    fn name(&self) -> &str {
+        "min"
+    }
+}
+
+pub struct MaxProcessor;
+
+impl<T: Environment> Procedure<T> for MaxProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        extremum(<Self as Procedure<T>>::name(self), &args, Ordering::Greater)
+    }
+
+    fn name(&self) -> &str {
+        "max"
+    }
+}
+
+pub struct GcdProcessor;
+
+impl<T: Environment> Procedure<T> for GcdProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let integers = integers(<Self as Procedure<T>>::name(self), &args)?;
+        let gcd = integers
+            .into_iter()
+            .fold(Number::from(0), |gcd, integer| gcd.gcd(integer));
+        Ok(Value::Number(gcd))
+    }
+
+    fn name(&self) -> &str {
+        "gcd"
+    }
+}
+
+pub struct LcmProcessor;
+
+impl<T: Environment> Procedure<T> for LcmProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let integers = integers(<Self as Procedure<T>>::name(self), &args)?;
+        let lcm = integers
+            .into_iter()
+            .fold(Number::from(1), |lcm, integer| lcm.lcm(integer));
+        Ok(Value::Number(lcm))
+    }
+
+    fn name(&self) -> &str {
+        "lcm"
+    }
+}
+
+pub struct ExptProcessor;
+
+impl<T: Environment> Procedure<T> for ExptProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 2, &args)?;
+        let numbers = numbers(name, &args)?;
+        numbers[0].expt(numbers[1]).map(Value::Number)
+    }
+
+    fn name(&self) -> &str {
+        "expt"
+    }
+}
+
+pub struct ExpProcessor;
+
+impl<T: Environment> Procedure<T> for ExpProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        real_function(<Self as Procedure<T>>::name(self), &args, f64::exp)
+    }
+
+    fn name(&self) -> &str {
+        "exp"
+    }
+}
+
+/// `(log z)` is the natural logarithm, `(log z base)` the logarithm in the base.
+pub struct LogProcessor;
+
+impl<T: Environment> Procedure<T> for LogProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity_range(name, 1, 2, &args)?;
+        let numbers = numbers(name, &args)?;
+        let log = match numbers.as_slice() {
+            [number, base] => number.to_f64().ln() / base.to_f64().ln(),
+            _ => numbers[0].to_f64().ln(),
+        };
+        Ok(Value::Number(Number::from(log)))
+    }
+
+    fn name(&self) -> &str {
+        "log"
+    }
+}
+
+pub struct SqrtProcessor;
+
+impl<T: Environment> Procedure<T> for SqrtProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let number = number_arg(name, &args)?;
+        match number.sqrt() {
+            Some(root) => Ok(Value::Number(root)),
+            None => Err(type_mismatch(name, &args[0])),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "sqrt"
+    }
+}
+
+/// `(exact-integer-sqrt k)` returns the root `s` and the remainder `r` of `k` as two
+/// values, with `(+ (* s s) r)` equal to `k`.
+pub struct ExactIntegerSqrtProcessor;
+
+impl<T: Environment> Procedure<T> for ExactIntegerSqrtProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        let number = number_arg(name, &args)?;
+        match number.exact_integer_sqrt() {
+            Some((root, rest)) => Ok(Value::Values(vec![
+                Value::Number(root),
+                Value::Number(rest),
+            ])),
+            None => Err(type_mismatch(name, &args[0])),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "exact-integer-sqrt"
+    }
+}
+
+pub struct SinProcessor;
+
+impl<T: Environment> Procedure<T> for SinProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        real_function(<Self as Procedure<T>>::name(self), &args, f64::sin)
+    }
+
+    fn name(&self) -> &str {
+        "sin"
+    }
+}
+
+pub struct CosProcessor;
+
+impl<T: Environment> Procedure<T> for CosProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        real_function(<Self as Procedure<T>>::name(self), &args, f64::cos)
+    }
+
+    fn name(&self) -> &str {
+        "cos"
+    }
+}
+
+pub struct TanProcessor;
+
+impl<T: Environment> Procedure<T> for TanProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        real_function(<Self as Procedure<T>>::name(self), &args, f64::tan)
+    }
+
+    fn name(&self) -> &str {
+        "tan"
+    }
+}
+
+/// `(atan z)` is the arc tangent, `(atan y x)` the angle of the point `(x, y)`.
+pub struct AtanProcessor;
+
+impl<T: Environment> Procedure<T> for AtanProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity_range(name, 1, 2, &args)?;
+        let numbers = numbers(name, &args)?;
+        let atan = match numbers.as_slice() {
+            [y, x] => y.to_f64().atan2(x.to_f64()),
+            _ => numbers[0].to_f64().atan(),
+        };
+        Ok(Value::Number(Number::from(atan)))
+    }
+
+    fn name(&self) -> &str {
+        "atan"
+    }
+}
+
+pub struct FloorProcessor;
+
+impl<T: Environment> Procedure<T> for FloorProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Number(number.floor()))
+    }
+
+    fn name(&self) -> &str {
+        "floor"
+    }
+}
+
+pub struct CeilingProcessor;
+
+impl<T: Environment> Procedure<T> for CeilingProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Number(number.ceiling()))
+    }
+
+    fn name(&self) -> &str {
+        "ceiling"
+    }
+}
+
+pub struct RoundProcessor;
+
+impl<T: Environment> Procedure<T> for RoundProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Number(number.round()))
+    }
+
+    fn name(&self) -> &str {
+        "round"
+    }
+}
+
+pub struct TruncateProcessor;
+
+impl<T: Environment> Procedure<T> for TruncateProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let number = number_arg(<Self as Procedure<T>>::name(self), &args)?;
+        Ok(Value::Number(number.truncate()))
+    }
+
+    fn name(&self) -> &str {
+        "truncate"
+    }
+}
+
+/// `(number->string z radix)` writes the number in radix 2, 8, 10 or 16, 10 when
+/// omitted. Inexact numbers are only written in radix 10.
+pub struct NumberToStringProcessor;
+
+impl<T: Environment> Procedure<T> for NumberToStringProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity_range(name, 1, 2, &args)?;
+        let radix = radix(name, args.get(1))?;
+        match &args[0] {
+            Value::Number(number) => number
+                .to_string_radix(radix)
+                .map(Value::String)
+                .ok_or_else(|| type_mismatch(name, &args[0])),
+            other => Err(type_mismatch(name, other)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "number->string"
+    }
+}
+
+/// `(string->number string radix)` reads a number, returning false when the string
+/// is not one.
+pub struct StringToNumberProcessor;
+
+impl<T: Environment> Procedure<T> for StringToNumberProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity_range(name, 1, 2, &args)?;
+        let radix = radix(name, args.get(1))?;
+        match &args[0] {
+            Value::String(text) => Ok(Number::parse(text, radix)
+                .map(Value::Number)
+                .unwrap_or(Value::Boolean(false))),
+            other => Err(type_mismatch(name, other)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "string->number"
+    }
+}
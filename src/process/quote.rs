@@ -0,0 +1,129 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::{Environment, Scope},
+    errors::LispComputerError,
+    parse::{Expression, split_dotted_list},
+    value::{Symbol, Value},
+};
+
+use super::{Function, Tail};
+
+pub struct QuoteProcessor;
+
+impl<T: Environment> Function<T> for QuoteProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        _env: &T,
+        _scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        match args {
+            [datum] => datum.to_datum().map(Tail::Value),
+            _ => Err(LispComputerError::ArityMismatch(
+                <QuoteProcessor as Function<T>>::name(self).to_string(),
+                1,
+                args.len(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "quote"
+    }
+}
+
+pub struct QuasiquoteProcessor;
+
+impl<T: Environment> Function<T> for QuasiquoteProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        match args {
+            [template] => quasiquote(template, 1, env, scope).map(Tail::Value),
+            _ => Err(LispComputerError::ArityMismatch(
+                <QuasiquoteProcessor as Function<T>>::name(self).to_string(),
+                1,
+                args.len(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "quasiquote"
+    }
+}
+
+/// Returns the operand of `(name operand)`.
+fn special_form<'a>(expression: &'a Expression, name: &str) -> Option<&'a Expression> {
+    match expression {
+        Expression::List(list, _) => match list.as_slice() {
+            [Expression::Variable(head, _), operand] if head == name => Some(operand),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Builds the value of a quasiquote template. `depth` counts the enclosing
+/// quasiquotes, only unquotes at depth 1 are evaluated.
+fn quasiquote<T: Environment>(
+    template: &Expression,
+    depth: usize,
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Value, LispComputerError> {
+    if let Some(operand) = special_form(template, "unquote") {
+        return if depth == 1 {
+            operand.eval(env, scope)
+        } else {
+            Ok(Value::list([
+                Value::Symbol(Symbol::new("unquote")),
+                quasiquote(operand, depth - 1, env, scope)?,
+            ]))
+        };
+    }
+    if let Some(operand) = special_form(template, "quasiquote") {
+        return Ok(Value::list([
+            Value::Symbol(Symbol::new("quasiquote")),
+            quasiquote(operand, depth + 1, env, scope)?,
+        ]));
+    }
+    let Expression::List(expressions, _) = template else {
+        return template.to_datum();
+    };
+    let (items, tail) = split_dotted_list(expressions)?;
+    let mut values = Vec::new();
+    for item in items {
+        match special_form(item, "unquote-splicing") {
+            Some(operand) if depth == 1 => {
+                let spliced = operand.eval(env, scope)?;
+                match spliced.list_items() {
+                    Some(spliced) => values.extend(spliced),
+                    None => {
+                        return Err(LispComputerError::TypeMismatch1 {
+                            operation: "unquote-splicing".to_string(),
+                            left: spliced,
+                        });
+                    }
+                }
+            }
+            Some(operand) => values.push(Value::list([
+                Value::Symbol(Symbol::new("unquote-splicing")),
+                quasiquote(operand, depth - 1, env, scope)?,
+            ])),
+            None => values.push(quasiquote(item, depth, env, scope)?),
+        }
+    }
+    let tail = match tail {
+        Some(tail) => quasiquote(tail, depth, env, scope)?,
+        None => Value::Nil,
+    };
+    Ok(values
+        .into_iter()
+        .rev()
+        .fold(tail, |tail, value| Value::cons(value, tail)))
+}
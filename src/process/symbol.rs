@@ -0,0 +1,76 @@
+use crate::{
+    environment::Environment,
+    errors::LispComputerError,
+    value::{Symbol, Value},
+};
+
+use super::{Procedure, check_arity};
+
+pub struct SymbolProcessor;
+
+impl<T: Environment> Procedure<T> for SymbolProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        let value = args.remove(0);
+        Ok(Value::Boolean(matches!(value, Value::Symbol(_))))
+    }
+
+    fn name(&self) -> &str {
+        "symbol?"
+    }
+}
+
+pub struct SymbolToStringProcessor;
+
+impl<T: Environment> Procedure<T> for SymbolToStringProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        match args.remove(0) {
+            Value::Symbol(symbol) => Ok(Value::String(symbol.name().to_string())),
+            other => Err(LispComputerError::TypeMismatch1 {
+                operation: name.to_string(),
+                left: other,
+            }),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "symbol->string"
+    }
+}
+
+pub struct StringToSymbolProcessor;
+
+impl<T: Environment> Procedure<T> for StringToSymbolProcessor {
+    fn call(&self, mut args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 1, &args)?;
+        match args.remove(0) {
+            Value::String(string) => Ok(Value::Symbol(Symbol::new(&string))),
+            other => Err(LispComputerError::TypeMismatch1 {
+                operation: name.to_string(),
+                left: other,
+            }),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "string->symbol"
+    }
+}
+
+pub struct EqProcessor;
+
+impl<T: Environment> Procedure<T> for EqProcessor {
+    fn call(&self, args: Vec<Value>, _env: &T) -> Result<Value, LispComputerError> {
+        let name = <Self as Procedure<T>>::name(self);
+        check_arity(name, 2, &args)?;
+        Ok(Value::Boolean(args[0].is_eq(&args[1])))
+    }
+
+    fn name(&self) -> &str {
+        "eq?"
+    }
+}
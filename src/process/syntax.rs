@@ -0,0 +1,261 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::{Environment, Scope},
+    errors::LispComputerError,
+    parse::{Expression, Span},
+    value::{Lambda, Macro, SyntaxRules, Value},
+};
+
+use super::{Function, Procedure, Tail, check_arity, define_variable, lambda_params};
+
+/// Expands the form once if it is the use of a macro, returns `None` otherwise.
+pub fn macroexpand_1<T: Environment>(
+    form: &Expression,
+    env: &T,
+    scope: &Scope,
+) -> Result<Option<Expression>, LispComputerError> {
+    let Expression::List(items, _) = form else {
+        return Ok(None);
+    };
+    let [Expression::Variable(keyword, span), args @ ..] = items.as_slice() else {
+        return Ok(None);
+    };
+    match env.get_variable(keyword, scope) {
+        Some(Value::Macro(transformer)) => transformer.expand(keyword, args, span, env).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Expands the form until it is no longer the use of a macro. Subforms are left
+/// as they are.
+pub fn macroexpand<T: Environment>(
+    form: &Expression,
+    env: &T,
+    scope: &Scope,
+) -> Result<Expression, LispComputerError> {
+    let mut form = form.clone();
+    while let Some(expansion) = macroexpand_1(&form, env, scope)? {
+        form = expansion;
+    }
+    Ok(form)
+}
+
+/// `(syntax-rules (literal...) (pattern template)...)`, optionally with a custom
+/// ellipsis identifier before the literals, evaluates to a macro transformer.
+pub struct SyntaxRulesProcessor;
+
+impl<T: Environment> Function<T> for SyntaxRulesProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        _env: &T,
+        _scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let invalid = || {
+            LispComputerError::InvalidArguments(
+                <Self as Function<T>>::name(self).to_string(),
+                args.to_vec(),
+            )
+        };
+        let (ellipsis, literals, rules) = match args {
+            [
+                Expression::Variable(ellipsis, _),
+                Expression::List(literals, _),
+                rules @ ..,
+            ] => (ellipsis.as_str(), literals, rules),
+            [Expression::List(literals, _), rules @ ..] => ("...", literals, rules),
+            _ => return Err(invalid()),
+        };
+        let literals = literals
+            .iter()
+            .map(|literal| match literal {
+                Expression::Variable(name, _) => Ok(name.to_string()),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<String>, LispComputerError>>()?;
+        let rules = rules
+            .iter()
+            .map(|rule| match rule {
+                Expression::List(rule, _) => match rule.as_slice() {
+                    // the pattern holds at least the keyword position
+                    [pattern @ Expression::List(items, _), template] if !items.is_empty() => {
+                        Ok((pattern.clone(), template.clone()))
+                    }
+                    _ => Err(invalid()),
+                },
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<(Expression, Expression)>, LispComputerError>>()?;
+        let syntax_rules = SyntaxRules::new(ellipsis.to_string(), literals, rules);
+        Ok(Tail::Value(Value::Macro(Rc::new(Macro::SyntaxRules(
+            syntax_rules,
+        )))))
+    }
+
+    fn name(&self) -> &str {
+        "syntax-rules"
+    }
+}
+
+/// Evaluates a macro transformer, the spec of `define-syntax` and `let-syntax`.
+fn transformer<T: Environment>(
+    name: &str,
+    spec: &Expression,
+    env: &T,
+    scope: &Rc<Scope>,
+) -> Result<Value, LispComputerError> {
+    match spec.eval(env, scope)? {
+        transformer @ Value::Macro(_) => Ok(transformer),
+        other => Err(LispComputerError::TypeMismatch1 {
+            operation: name.to_string(),
+            left: other,
+        }),
+    }
+}
+
+pub struct DefineSyntaxProcessor;
+
+impl<T: Environment> Function<T> for DefineSyntaxProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let name = <Self as Function<T>>::name(self);
+        match args {
+            [Expression::Variable(keyword, _), spec] => {
+                let transformer = transformer(name, spec, env, scope)?;
+                define_variable(keyword, transformer, env, scope);
+                Ok(Tail::Value(Value::Nil))
+            }
+            _ => Err(LispComputerError::InvalidArguments(
+                name.to_string(),
+                args.to_vec(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "define-syntax"
+    }
+}
+
+/// `(let-syntax ((keyword spec)...) body...)` binds macros for the body only.
+pub struct LetSyntaxProcessor;
+
+impl<T: Environment> Function<T> for LetSyntaxProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        let name = <Self as Function<T>>::name(self);
+        let invalid = || LispComputerError::InvalidArguments(name.to_string(), args.to_vec());
+        let [Expression::List(bindings, _), body @ .., last] = args else {
+            return Err(invalid());
+        };
+        let syntax_scope = Scope::new(scope);
+        for binding in bindings {
+            match binding {
+                Expression::List(binding, _) => match binding.as_slice() {
+                    [Expression::Variable(keyword, _), spec] => {
+                        let transformer = transformer(name, spec, env, scope)?;
+                        syntax_scope.insert(keyword.to_string(), transformer);
+                    }
+                    _ => return Err(invalid()),
+                },
+                _ => return Err(invalid()),
+            }
+        }
+        for expression in body {
+            expression.eval_values(env, &syntax_scope)?;
+        }
+        Ok(Tail::Eval(last.clone(), syntax_scope))
+    }
+
+    fn name(&self) -> &str {
+        "let-syntax"
+    }
+}
+
+/// `(defmacro name (params...) body...)` defines a procedural macro. The body receives
+/// the argument forms unevaluated, as quoted data, and returns the form evaluated
+/// in place of the call.
+pub struct DefmacroProcessor;
+
+impl<T: Environment> Function<T> for DefmacroProcessor {
+    fn process(
+        &self,
+        args: &[Expression],
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<Tail, LispComputerError> {
+        match args {
+            [
+                Expression::Variable(name, _),
+                formals @ (Expression::List(..) | Expression::Variable(..)),
+                body @ ..,
+            ] if !body.is_empty() => {
+                let params = lambda_params(formals)?;
+                let lambda = Lambda::new(params, body.into(), scope).with_name(name);
+                let transformer = Macro::Procedure(Rc::new(lambda));
+                define_variable(name, Value::Macro(Rc::new(transformer)), env, scope);
+                Ok(Tail::Value(Value::Nil))
+            }
+            _ => Err(LispComputerError::InvalidArguments(
+                <Self as Function<T>>::name(self).to_string(),
+                args.to_vec(),
+            )),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "defmacro"
+    }
+}
+
+/// Expands a quoted form with the global macros, as `macroexpand-1` once and as
+/// `macroexpand` repeatedly.
+fn expand_datum<T: Environment>(
+    name: &str,
+    args: &[Value],
+    once: bool,
+    env: &T,
+) -> Result<Value, LispComputerError> {
+    check_arity(name, 1, args)?;
+    let form = Expression::from_datum(&args[0], &Span::default())?;
+    let scope = Scope::default();
+    let expansion = if once {
+        macroexpand_1(&form, env, &scope)?.unwrap_or(form)
+    } else {
+        macroexpand(&form, env, &scope)?
+    };
+    expansion.to_datum()
+}
+
+pub struct MacroexpandOnceProcessor;
+
+impl<T: Environment> Procedure<T> for MacroexpandOnceProcessor {
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        expand_datum(<Self as Procedure<T>>::name(self), &args, true, env)
+    }
+
+    fn name(&self) -> &str {
+        "macroexpand-1"
+    }
+}
+
+pub struct MacroexpandProcessor;
+
+impl<T: Environment> Procedure<T> for MacroexpandProcessor {
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        expand_datum(<Self as Procedure<T>>::name(self), &args, false, env)
+    }
+
+    fn name(&self) -> &str {
+        "macroexpand"
+    }
+}
@@ -0,0 +1,35 @@
+use rustyline::{
+    Helper,
+    completion::Completer,
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::{ValidationContext, ValidationResult, Validator},
+};
+
+use crate::parse::is_incomplete;
+
+/// Keeps the line editor reading while the input has unbalanced parentheses or an
+/// unterminated string.
+pub struct InputValidator;
+
+impl Validator for InputValidator {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if is_incomplete(ctx.input()) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+impl Completer for InputValidator {
+    type Candidate = String;
+}
+
+impl Hinter for InputValidator {
+    type Hint = String;
+}
+
+impl Highlighter for InputValidator {}
+
+impl Helper for InputValidator {}
@@ -1,16 +1,41 @@
+mod error;
 mod lambda;
+mod macros;
+mod number;
+mod pair;
+mod symbol;
+mod syntax_rules;
 
-use std::fmt::Display;
+use std::{cell::RefCell, fmt::Display, rc::Rc};
 
-pub use lambda::Lambda;
+use crate::errors::LispComputerError;
+
+pub use error::ErrorObject;
+pub use lambda::{Body, Lambda, Params};
+pub use macros::Macro;
+pub use number::Number;
+pub use pair::Pair;
+pub use symbol::Symbol;
+pub use syntax_rules::SyntaxRules;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     String(String),
-    Number(f64),
+    Number(number::Number),
     Boolean(bool),
+    Symbol(symbol::Symbol),
     Nil,
-    Lambda(lambda::Lambda),
+    Lambda(Rc<lambda::Lambda>),
+    Builtin(symbol::Symbol),
+    Pair(Rc<pair::Pair>),
+    Error(Rc<error::ErrorObject>),
+    Macro(Rc<macros::Macro>),
+    /// a mutable cell made by `box`
+    Box(Rc<RefCell<Value>>),
+    /// the results of `values`, other than a single one
+    Values(Vec<Value>),
+    /// a `letrec` variable whose init has not been evaluated yet
+    Unassigned,
 }
 
 impl Display for Value {
@@ -19,8 +44,24 @@ impl Display for Value {
             Value::String(s) => write!(f, "\"{}\"", s),
             Value::Number(n) => write!(f, "{}", n),
             Value::Boolean(b) => write!(f, "{}", b),
+            Value::Symbol(name) => write!(f, "{}", name),
             Value::Nil => write!(f, "nil"),
             Value::Lambda(l) => write!(f, "<lambda>:{}", l),
+            Value::Builtin(name) => write!(f, "<builtin>:{}", name),
+            Value::Pair(p) => write!(f, "{}", p),
+            Value::Error(e) => write!(f, "<error>:{}", e),
+            Value::Macro(_) => write!(f, "<macro>"),
+            Value::Box(value) => write!(f, "#&{}", value.borrow()),
+            Value::Values(values) => write!(
+                f,
+                "{}",
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            Value::Unassigned => write!(f, "<unassigned>"),
         }
     }
 }
@@ -33,4 +74,72 @@ impl Value {
             _ => true,
         }
     }
+    /// Identity comparison as done by `eq?`: pairs, lambdas, error objects, macros
+    /// and boxes must be the same object, atoms compare by value.
+    pub fn is_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Pair(left), Value::Pair(right)) => Rc::ptr_eq(left, right),
+            (Value::Lambda(left), Value::Lambda(right)) => Rc::ptr_eq(left, right),
+            (Value::Error(left), Value::Error(right)) => Rc::ptr_eq(left, right),
+            (Value::Macro(left), Value::Macro(right)) => Rc::ptr_eq(left, right),
+            (Value::Box(left), Value::Box(right)) => Rc::ptr_eq(left, right),
+            (left, right) => left == right,
+        }
+    }
+    /// Whether the values are `eqv?`: like [`Value::is_eq`], except that strings are
+    /// copied rather than shared, so two strings are never the same object.
+    pub fn is_eqv(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::String(_), Value::String(_)) => false,
+            (left, right) => left.is_eq(right),
+        }
+    }
+    pub fn cons(car: Value, cdr: Value) -> Value {
+        Value::Pair(Rc::new(Pair { car, cdr }))
+    }
+    /// Builds a proper list, `nil` being the empty list.
+    pub fn list<I>(items: I) -> Value
+    where
+        I: IntoIterator<Item = Value>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        items
+            .into_iter()
+            .rev()
+            .fold(Value::Nil, |tail, item| Value::cons(item, tail))
+    }
+    /// The results carried by the value, several for `values` and itself otherwise.
+    pub fn into_values(self) -> Vec<Value> {
+        match self {
+            Value::Values(values) => values,
+            value => vec![value],
+        }
+    }
+    /// The value itself where a single value is expected, an arity error for the
+    /// zero or several results of `values`.
+    pub fn single(self) -> Result<Value, LispComputerError> {
+        match self {
+            Value::Values(values) => Err(LispComputerError::ArityMismatch(
+                "values".to_string(),
+                1,
+                values.len(),
+            )),
+            value => Ok(value),
+        }
+    }
+    /// Returns the elements of a proper list, or `None` for anything else.
+    pub fn list_items(&self) -> Option<Vec<Value>> {
+        let mut items = Vec::new();
+        let mut tail = self;
+        loop {
+            match tail {
+                Value::Nil => return Some(items),
+                Value::Pair(pair) => {
+                    items.push(pair.car.clone());
+                    tail = &pair.cdr;
+                }
+                _ => return None,
+            }
+        }
+    }
 }
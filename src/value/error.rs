@@ -0,0 +1,20 @@
+use std::fmt::Display;
+
+use super::Value;
+
+/// The condition raised by `error`, or made from a built-in error caught by `guard`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorObject {
+    pub message: String,
+    pub irritants: Vec<Value>,
+}
+
+impl Display for ErrorObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        for irritant in &self.irritants {
+            write!(f, " {}", irritant)?;
+        }
+        Ok(())
+    }
+}
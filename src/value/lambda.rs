@@ -1,58 +1,179 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{fmt::Display, rc::Rc};
 
 use crate::{
-    environment::Environment,
+    environment::{Environment, Scope},
     errors::LispComputerError,
     parse::Expression,
-    process::{Function, process_expression_list},
+    process::{Procedure, Tail},
+    value::Value,
 };
 
-use super::Value;
+/// The formals of a lambda: `(a b (c default) . rest)` binds `a` and `b`, `c` to
+/// its default when the argument is missing, and the remaining arguments as a
+/// list to `rest`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Params {
+    pub required: Vec<String>,
+    pub optional: Vec<(String, Expression)>,
+    pub rest: Option<String>,
+}
+
+impl Display for Params {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut formals: Vec<String> = self.required.clone();
+        for (name, default) in &self.optional {
+            formals.push(format!("({name} {default})"));
+        }
+        match (&self.rest, formals.is_empty()) {
+            (Some(rest), true) => write!(f, "{rest}"),
+            (Some(rest), false) => write!(f, "({} . {rest})", formals.join(" ")),
+            (None, _) => write!(f, "({})", formals.join(" ")),
+        }
+    }
+}
+
+impl From<Vec<String>> for Params {
+    fn from(required: Vec<String>) -> Self {
+        Params {
+            required,
+            ..Params::default()
+        }
+    }
+}
 
-#[derive(Debug, Clone, PartialEq)]
+impl Params {
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.required.len()
+            && (self.rest.is_some() || count <= self.required.len() + self.optional.len())
+    }
+    /// The argument count to report when `count` arguments are not accepted.
+    pub fn expected(&self, count: usize) -> usize {
+        if count > self.required.len() {
+            self.required.len() + self.optional.len()
+        } else {
+            self.required.len()
+        }
+    }
+    /// Binds accepted arguments in `scope`. Defaults of missing optional arguments
+    /// are evaluated there, after the parameters before them are bound.
+    pub fn bind<T: Environment>(
+        &self,
+        args: Vec<Value>,
+        env: &T,
+        scope: &Rc<Scope>,
+    ) -> Result<(), LispComputerError> {
+        let mut args = args.into_iter();
+        for (param, arg) in self.required.iter().zip(&mut args) {
+            scope.insert(param.to_string(), arg);
+        }
+        for (param, default) in &self.optional {
+            let value = match args.next() {
+                Some(arg) => arg,
+                None => default.eval(env, scope)?,
+            };
+            scope.insert(param.to_string(), value);
+        }
+        if let Some(rest) = &self.rest {
+            scope.insert(rest.to_string(), Value::list(args));
+        }
+        Ok(())
+    }
+}
+
+/// The expressions of a lambda body, shared by the calls.
+pub type Body = Rc<[Expression]>;
+
+fn display_body(body: &[Expression]) -> String {
+    body.iter()
+        .map(|expression| expression.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[derive(Debug, Clone)]
 pub struct Lambda {
-    params: Vec<String>,
-    body: Vec<Expression>,
+    /// set for lambdas introduced by `define` or a named `let`
+    name: Option<Rc<str>>,
+    /// the formals and body expressions, evaluated in order; `case-lambda`
+    /// makes several, the first accepting the argument count is used
+    clauses: Vec<(Params, Body)>,
+    /// scope the lambda was created in
+    closure: Rc<Scope>,
+}
+
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Self) -> bool {
+        self.clauses == other.clauses && Rc::ptr_eq(&self.closure, &other.closure)
+    }
 }
 
 impl Display for Lambda {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "(lambda ({})(", self.params.join(" "))?;
-        for expr in &self.body {
-            write!(f, " {}", expr)?;
+        match self.clauses.as_slice() {
+            [(params, body)] => write!(f, "(lambda {params} {})", display_body(body)),
+            clauses => {
+                write!(f, "(case-lambda")?;
+                for (params, body) in clauses {
+                    write!(f, " ({params} {})", display_body(body))?;
+                }
+                write!(f, ")")
+            }
         }
-        write!(f, "))")
     }
 }
 
 impl Lambda {
-    pub fn new(params: Vec<String>, body: Vec<Expression>) -> Self {
-        Lambda { params, body }
-    }
-}
-
-impl<T: Environment> Function<T> for Lambda {
-    fn process(
+    /// Binds the arguments in a new frame and returns the body, left for the caller
+    /// to evaluate in that frame.
+    pub fn tail_call<T: Environment>(
         &self,
-        args: &[Expression],
+        args: Vec<Value>,
         env: &T,
-        variables: &HashMap<&str, Value>,
-    ) -> Result<super::Value, LispComputerError> {
-        if args.len() != self.params.len() {
+    ) -> Result<Tail, LispComputerError> {
+        let Some((params, body)) = self
+            .clauses
+            .iter()
+            .find(|(params, _)| params.accepts(args.len()))
+        else {
+            let expected = self
+                .clauses
+                .first()
+                .map_or(0, |(params, _)| params.expected(args.len()));
             return Err(LispComputerError::ArityMismatch(
-                <Lambda as Function<T>>::name(self).to_string(),
-                self.params.len(),
+                self.name().to_string(),
+                expected,
                 args.len(),
             ));
+        };
+        let call_scope = Scope::new(&self.closure);
+        params.bind(args, env, &call_scope)?;
+        Ok(Tail::Call(self.name.clone(), body.clone(), call_scope))
+    }
+    pub fn new(params: impl Into<Params>, body: Body, closure: &Rc<Scope>) -> Self {
+        Self::with_clauses(vec![(params.into(), body)], closure)
+    }
+    pub fn with_clauses(clauses: Vec<(Params, Body)>, closure: &Rc<Scope>) -> Self {
+        Lambda {
+            name: None,
+            clauses,
+            closure: closure.clone(),
         }
-        let mut new_variables = variables.clone();
-        for (param, arg) in self.params.iter().zip(args) {
-            new_variables.insert(param.as_str(), arg.eval(env, variables)?);
-        }
-        process_expression_list(&self.body, env, &new_variables)
+    }
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("lambda-function")
+    }
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(Rc::from(name));
+        self
+    }
+}
+
+impl<T: Environment> Procedure<T> for Lambda {
+    fn call(&self, args: Vec<Value>, env: &T) -> Result<Value, LispComputerError> {
+        self.tail_call(args, env)?.eval(env)
     }
 
     fn name(&self) -> &str {
-        "lambda-function"
+        Lambda::name(self)
     }
 }
@@ -0,0 +1,41 @@
+use std::rc::Rc;
+
+use crate::{
+    environment::Environment,
+    errors::LispComputerError,
+    parse::{Expression, Span},
+    process::Procedure,
+};
+
+use super::{Lambda, SyntaxRules, Value};
+
+/// A transformer bound to a keyword, expanded wherever the keyword heads a form.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Macro {
+    SyntaxRules(SyntaxRules),
+    /// a `defmacro`: a lambda from the unevaluated argument forms, as quoted data,
+    /// to the form replacing the call
+    Procedure(Rc<Lambda>),
+}
+
+impl Macro {
+    pub fn expand<T: Environment>(
+        &self,
+        keyword: &str,
+        args: &[Expression],
+        span: &Span,
+        env: &T,
+    ) -> Result<Expression, LispComputerError> {
+        match self {
+            Macro::SyntaxRules(syntax_rules) => syntax_rules.expand(keyword, args),
+            Macro::Procedure(lambda) => {
+                let args = args
+                    .iter()
+                    .map(Expression::to_datum)
+                    .collect::<Result<Vec<Value>, LispComputerError>>()?;
+                let expansion = lambda.call(args, env)?;
+                Expression::from_datum(&expansion, span)
+            }
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use std::{cmp::Ordering, fmt::Display, rc::Rc};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, One, ToPrimitive, Zero};
+
+use crate::errors::LispComputerError;
+
+/// The most bits of an exact power `expt` computes. Larger powers would take
+/// too long and too much memory.
+const MAX_EXACT_POWER_BITS: u64 = 1 << 24;
+
+/// A number of the numeric tower. Exact integers are `i64` until an operation
+/// overflows them, then arbitrary precision; other exact numbers are rationals;
+/// inexact numbers are `f64`.
+///
+/// Exact numbers are kept normalized, a big integer never fits in an `i64` and a
+/// rational is never an integer, so the derived equality compares exact numbers by
+/// value. It does not equate exact and inexact numbers, as `=` does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Big(Rc<BigInt>),
+    Rational(Rc<BigRational>),
+    Real(f64),
+}
+
+/// Two numbers brought to the representation of the less precise one: inexact is
+/// contagious, then rationals, then big integers.
+enum Operands {
+    Integer(i64, i64),
+    Big(BigInt, BigInt),
+    Rational(BigRational, BigRational),
+    Real(f64, f64),
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number::Integer(value)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Real(value)
+    }
+}
+
+impl From<BigInt> for Number {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(value) => Number::Integer(value),
+            None => Number::Big(Rc::new(value)),
+        }
+    }
+}
+
+impl From<BigRational> for Number {
+    fn from(value: BigRational) -> Self {
+        if value.is_integer() {
+            Number::from(value.to_integer())
+        } else {
+            Number::Rational(Rc::new(value))
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Integer(value) => write!(f, "{value}"),
+            Number::Big(value) => write!(f, "{value}"),
+            Number::Rational(value) => write!(f, "{value}"),
+            Number::Real(value) if value.is_nan() => write!(f, "+nan.0"),
+            Number::Real(value) if value.is_infinite() => {
+                write!(f, "{}inf.0", if *value > 0.0 { "+" } else { "-" })
+            }
+            // always with a fraction or an exponent, so it reads back as inexact
+            Number::Real(value) => write!(f, "{value:?}"),
+        }
+    }
+}
+
+impl Number {
+    /// Reads the external representation of a number: an integer or a rational
+    /// `n/d` in the given radix, or in radix 10 a decimal with an optional
+    /// exponent, `+inf.0`, `-inf.0` or `+nan.0`.
+    pub fn parse(text: &str, radix: u32) -> Option<Number> {
+        let is_integer = |text: &str| {
+            let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+            !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix))
+        };
+        let integer = |text: &str| BigInt::parse_bytes(text.as_bytes(), radix);
+        match text {
+            "+inf.0" => return Some(Number::Real(f64::INFINITY)),
+            "-inf.0" => return Some(Number::Real(f64::NEG_INFINITY)),
+            "+nan.0" | "-nan.0" => return Some(Number::Real(f64::NAN)),
+            _ => {}
+        }
+        if let Some((numer, denom)) = text.split_once('/') {
+            if !is_integer(numer) || !denom.chars().all(|c| c.is_digit(radix)) {
+                return None;
+            }
+            let denom = integer(denom)?;
+            if denom.is_zero() {
+                return None;
+            }
+            return Some(Number::from(BigRational::new(integer(numer)?, denom)));
+        }
+        if is_integer(text) {
+            return integer(text).map(Number::from);
+        }
+        // the float parser of the standard library also reads words like `inf`
+        let is_decimal = text.chars().any(|c| c.is_ascii_digit())
+            && text
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'));
+        if radix != 10 || !is_decimal {
+            return None;
+        }
+        text.parse().ok().map(Number::Real)
+    }
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, Number::Real(_))
+    }
+    pub fn is_integer(&self) -> bool {
+        match self {
+            Number::Integer(_) | Number::Big(_) => true,
+            Number::Rational(_) => false,
+            Number::Real(value) => value.is_finite() && value.fract() == 0.0,
+        }
+    }
+    pub fn is_rational(&self) -> bool {
+        match self {
+            Number::Real(value) => value.is_finite(),
+            _ => true,
+        }
+    }
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Integer(value) => *value == 0,
+            Number::Real(value) => *value == 0.0,
+            // normalized big integers and rationals are never zero
+            _ => false,
+        }
+    }
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Integer(value) => *value as f64,
+            Number::Big(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Rational(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Real(value) => *value,
+        }
+    }
+    /// The value as a count or an index, for exact non-negative integers.
+    pub fn to_usize(&self) -> Option<usize> {
+        match self {
+            Number::Integer(value) => usize::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+    /// The exact number equal to the value, none for infinities and NaN.
+    pub fn to_exact(&self) -> Option<Number> {
+        match self {
+            Number::Real(value) => BigRational::from_f64(*value).map(Number::from),
+            exact => Some(exact.clone()),
+        }
+    }
+    pub fn to_inexact(&self) -> Number {
+        Number::Real(self.to_f64())
+    }
+    fn to_big(&self) -> BigInt {
+        match self {
+            Number::Integer(value) => BigInt::from(*value),
+            Number::Big(value) => value.as_ref().clone(),
+            _ => unreachable!("only called on exact integers"),
+        }
+    }
+    fn to_rational(&self) -> BigRational {
+        match self {
+            Number::Rational(value) => value.as_ref().clone(),
+            Number::Real(_) => unreachable!("only called on exact numbers"),
+            integer => BigRational::from_integer(integer.to_big()),
+        }
+    }
+    fn operands(&self, other: &Number) -> Operands {
+        match (self, other) {
+            (Number::Integer(left), Number::Integer(right)) => Operands::Integer(*left, *right),
+            (Number::Real(_), _) | (_, Number::Real(_)) => {
+                Operands::Real(self.to_f64(), other.to_f64())
+            }
+            (Number::Rational(_), _) | (_, Number::Rational(_)) => {
+                Operands::Rational(self.to_rational(), other.to_rational())
+            }
+            _ => Operands::Big(self.to_big(), other.to_big()),
+        }
+    }
+    /// Applies an operation in the representation of the operands, `integer`
+    /// returning `None` when `i64` overflows.
+    fn arithmetic(
+        &self,
+        other: &Number,
+        integer: fn(i64, i64) -> Option<i64>,
+        big: fn(BigInt, BigInt) -> BigInt,
+        rational: fn(BigRational, BigRational) -> BigRational,
+        real: fn(f64, f64) -> f64,
+    ) -> Number {
+        match self.operands(other) {
+            Operands::Integer(left, right) => match integer(left, right) {
+                Some(result) => Number::Integer(result),
+                None => Number::from(big(BigInt::from(left), BigInt::from(right))),
+            },
+            Operands::Big(left, right) => Number::from(big(left, right)),
+            Operands::Rational(left, right) => Number::from(rational(left, right)),
+            Operands::Real(left, right) => Number::Real(real(left, right)),
+        }
+    }
+    pub fn add(&self, other: &Number) -> Number {
+        self.arithmetic(
+            other,
+            i64::checked_add,
+            |a, b| a + b,
+            |a, b| a + b,
+            |a, b| a + b,
+        )
+    }
+    pub fn sub(&self, other: &Number) -> Number {
+        self.arithmetic(
+            other,
+            i64::checked_sub,
+            |a, b| a - b,
+            |a, b| a - b,
+            |a, b| a - b,
+        )
+    }
+    pub fn mul(&self, other: &Number) -> Number {
+        self.arithmetic(
+            other,
+            i64::checked_mul,
+            |a, b| a * b,
+            |a, b| a * b,
+            |a, b| a * b,
+        )
+    }
+    /// Divides, exactly when both numbers are exact. Returns `None` for an exact
+    /// division by zero.
+    pub fn div(&self, other: &Number) -> Option<Number> {
+        match self.operands(other) {
+            Operands::Real(left, right) => Some(Number::Real(left / right)),
+            _ if other.is_zero() => None,
+            _ => Some(Number::from(self.to_rational() / other.to_rational())),
+        }
+    }
+    /// Divides two integers with `integer`, `big` or `real` depending on their
+    /// representation. Returns `None` for a division by zero.
+    fn integer_division(
+        &self,
+        other: &Number,
+        integer: fn(i64, i64) -> Option<i64>,
+        big: fn(&BigInt, &BigInt) -> BigInt,
+        real: fn(f64, f64) -> f64,
+    ) -> Option<Number> {
+        if other.is_zero() {
+            return None;
+        }
+        Some(match self.operands(other) {
+            Operands::Integer(left, right) => match integer(left, right) {
+                Some(result) => Number::Integer(result),
+                None => Number::from(big(&BigInt::from(left), &BigInt::from(right))),
+            },
+            Operands::Big(left, right) => Number::from(big(&left, &right)),
+            Operands::Real(left, right) => Number::Real(real(left, right)),
+            Operands::Rational(..) => unreachable!("only called on integers"),
+        })
+    }
+    /// The quotient of two integers, rounded toward zero.
+    pub fn quotient(&self, other: &Number) -> Option<Number> {
+        self.integer_division(
+            other,
+            i64::checked_div,
+            |a, b| a / b,
+            |a, b| (a / b).trunc(),
+        )
+    }
+    /// The remainder of [`Number::quotient`], with the sign of the dividend.
+    pub fn remainder(&self, other: &Number) -> Option<Number> {
+        self.integer_division(other, i64::checked_rem, |a, b| a % b, |a, b| a % b)
+    }
+    /// The remainder of the division rounded toward negative infinity, with the sign
+    /// of the divisor.
+    pub fn modulo(&self, other: &Number) -> Option<Number> {
+        self.integer_division(
+            other,
+            |a, b| {
+                a.checked_rem(b).map(|r| {
+                    if r != 0 && (r < 0) != (b < 0) {
+                        r + b
+                    } else {
+                        r
+                    }
+                })
+            },
+            |a, b| a.mod_floor(b),
+            |a, b| a - b * (a / b).floor(),
+        )
+    }
+    /// Applies an operation on integers exactly, the result being inexact if either
+    /// integer is.
+    fn integer_operation(&self, other: &Number, big: fn(&BigInt, &BigInt) -> BigInt) -> Number {
+        let exact = |number: &Number| number.to_exact().map_or_else(BigInt::zero, |n| n.to_big());
+        let result = Number::from(big(&exact(self), &exact(other)));
+        if self.is_exact() && other.is_exact() {
+            result
+        } else {
+            result.to_inexact()
+        }
+    }
+    pub fn gcd(&self, other: &Number) -> Number {
+        self.integer_operation(other, BigInt::gcd)
+    }
+    pub fn lcm(&self, other: &Number) -> Number {
+        self.integer_operation(other, BigInt::lcm)
+    }
+    pub fn abs(&self) -> Number {
+        if self.compare(&Number::Integer(0)) == Some(Ordering::Less) {
+            Number::Integer(0).sub(self)
+        } else {
+            self.clone()
+        }
+    }
+    /// Raises to a power, exactly for an exact base and a small exact integer
+    /// exponent. Exact zero cannot be raised to a negative power, and exact powers
+    /// longer than [`MAX_EXACT_POWER_BITS`] are not computed.
+    pub fn expt(&self, exponent: &Number) -> Result<Number, LispComputerError> {
+        if let Number::Integer(exponent) = exponent
+            && let Ok(exponent) = i32::try_from(*exponent)
+            && self.is_exact()
+        {
+            if exponent < 0 && self.is_zero() {
+                return Err(LispComputerError::DivisionByZero("expt".to_string()));
+            }
+            let base = self.to_rational();
+            // the power has at least this many bits in its numerator or denominator
+            let bits = base.numer().bits().max(base.denom().bits()) - 1;
+            if bits.saturating_mul(u64::from(exponent.unsigned_abs())) > MAX_EXACT_POWER_BITS {
+                return Err(LispComputerError::ExactResultTooLarge("expt".to_string()));
+            }
+            return Ok(Number::from(base.pow(exponent)));
+        }
+        Ok(Number::Real(self.to_f64().powf(exponent.to_f64())))
+    }
+    /// The square root, exact for exact squares of integers and rationals. Returns
+    /// `None` for negative numbers.
+    pub fn sqrt(&self) -> Option<Number> {
+        let exact_root = |value: &BigInt| {
+            let root = value.sqrt();
+            (&root * &root == *value).then_some(root)
+        };
+        if self.compare(&Number::Integer(0)) == Some(Ordering::Less) {
+            return None;
+        }
+        let root = match self {
+            Number::Real(_) => None,
+            Number::Rational(value) => exact_root(value.numer())
+                .zip(exact_root(value.denom()))
+                .map(|(numer, denom)| Number::from(BigRational::new(numer, denom))),
+            integer => exact_root(&integer.to_big()).map(Number::from),
+        };
+        Some(root.unwrap_or_else(|| Number::Real(self.to_f64().sqrt())))
+    }
+    /// The root `s` and remainder `r` with `s * s + r` equal to the number, for
+    /// exact non-negative integers.
+    pub fn exact_integer_sqrt(&self) -> Option<(Number, Number)> {
+        match self {
+            Number::Integer(_) | Number::Big(_)
+                if self.compare(&Number::Integer(0)) != Some(Ordering::Less) =>
+            {
+                let value = self.to_big();
+                let root = value.sqrt();
+                let rest = &value - &root * &root;
+                Some((Number::from(root), Number::from(rest)))
+            }
+            _ => None,
+        }
+    }
+    fn rounded(&self, rational: fn(&BigRational) -> BigRational, real: fn(f64) -> f64) -> Number {
+        match self {
+            Number::Rational(value) => Number::from(rational(value)),
+            Number::Real(value) => Number::Real(real(*value)),
+            integer => integer.clone(),
+        }
+    }
+    pub fn floor(&self) -> Number {
+        self.rounded(BigRational::floor, f64::floor)
+    }
+    pub fn ceiling(&self) -> Number {
+        self.rounded(BigRational::ceil, f64::ceil)
+    }
+    pub fn truncate(&self) -> Number {
+        self.rounded(BigRational::trunc, f64::trunc)
+    }
+    /// Rounds to the nearest integer, to the even one when halfway between two.
+    pub fn round(&self) -> Number {
+        self.rounded(
+            |value| {
+                let floor = value.floor();
+                let half = BigRational::new(BigInt::one(), BigInt::from(2));
+                match (value - &floor).cmp(&half) {
+                    Ordering::Less => floor,
+                    Ordering::Greater => floor + BigInt::one(),
+                    Ordering::Equal if floor.to_integer().is_even() => floor,
+                    Ordering::Equal => floor + BigInt::one(),
+                }
+            },
+            f64::round_ties_even,
+        )
+    }
+    /// Writes the number in radix 2 to 36. Inexact numbers are only written in
+    /// radix 10.
+    pub fn to_string_radix(&self, radix: u32) -> Option<String> {
+        match self {
+            _ if radix == 10 => Some(self.to_string()),
+            Number::Integer(value) => Some(BigInt::from(*value).to_str_radix(radix)),
+            Number::Big(value) => Some(value.to_str_radix(radix)),
+            Number::Rational(value) => Some(format!(
+                "{}/{}",
+                value.numer().to_str_radix(radix),
+                value.denom().to_str_radix(radix)
+            )),
+            Number::Real(_) => None,
+        }
+    }
+    /// Compares numerically, exact and inexact numbers alike. `None` when either is
+    /// NaN.
+    pub fn compare(&self, other: &Number) -> Option<Ordering> {
+        match self.operands(other) {
+            Operands::Integer(left, right) => Some(left.cmp(&right)),
+            Operands::Big(left, right) => Some(left.cmp(&right)),
+            Operands::Rational(left, right) => Some(left.cmp(&right)),
+            Operands::Real(left, right) => left.partial_cmp(&right),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::{fmt::Display, rc::Rc};
+
+use super::Value;
+
+#[derive(Debug, Clone)]
+pub struct Pair {
+    pub car: Value,
+    pub cdr: Value,
+}
+
+/// Compares the lists element by element, following the `cdr` spine in a loop so
+/// long lists do not exhaust the stack.
+impl PartialEq for Pair {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut left, mut right) = (self, other);
+        loop {
+            if left.car != right.car {
+                return false;
+            }
+            match (&left.cdr, &right.cdr) {
+                (Value::Pair(l), Value::Pair(r)) => {
+                    left = l;
+                    right = r;
+                }
+                (l, r) => return l == r,
+            }
+        }
+    }
+}
+
+/// Unlinks the `cdr` spine in a loop, as far as this pair holds the only reference
+/// to the rest of the list, instead of dropping it recursively.
+impl Drop for Pair {
+    fn drop(&mut self) {
+        let mut tail = std::mem::replace(&mut self.cdr, Value::Nil);
+        while let Value::Pair(pair) = tail {
+            match Rc::try_unwrap(pair) {
+                Ok(mut pair) => tail = std::mem::replace(&mut pair.cdr, Value::Nil),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl Display for Pair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}", self.car)?;
+        let mut tail = &self.cdr;
+        loop {
+            match tail {
+                Value::Pair(pair) => {
+                    write!(f, " {}", pair.car)?;
+                    tail = &pair.cdr;
+                }
+                Value::Nil => break,
+                other => {
+                    write!(f, " . {}", other)?;
+                    break;
+                }
+            }
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,39 @@
+use std::{cell::RefCell, collections::HashSet, fmt::Display, rc::Rc};
+
+thread_local! {
+    static SYMBOLS: RefCell<HashSet<Rc<str>>> = RefCell::default();
+}
+
+/// An interned name. Symbols with the same name share one allocation, so
+/// comparing them is a pointer comparison.
+#[derive(Debug, Clone)]
+pub struct Symbol(Rc<str>);
+
+impl Symbol {
+    pub fn new(name: &str) -> Self {
+        SYMBOLS.with(|symbols| {
+            let mut symbols = symbols.borrow_mut();
+            if let Some(symbol) = symbols.get(name) {
+                return Symbol(symbol.clone());
+            }
+            let symbol: Rc<str> = Rc::from(name);
+            symbols.insert(symbol.clone());
+            Symbol(symbol)
+        })
+    }
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
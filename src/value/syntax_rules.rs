@@ -0,0 +1,582 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    errors::LispComputerError,
+    parse::{Expression, split_dotted_list},
+};
+
+thread_local! {
+    /// Numbers the expansions, so the identifiers each one introduces are distinct.
+    static EXPANSIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A macro transformer made by `syntax-rules`. Each rule is a pattern matched
+/// against the form and the template the form is rewritten into.
+///
+/// Expansion is hygienic for bindings: identifiers the template introduces in a
+/// binding position (a `lambda` or `case-lambda` parameter, a `let` or `do` variable,
+/// an internal `define`) are renamed within the scope of that binding, so they
+/// never capture identifiers passed in by the user. Free identifiers, quoted data
+/// and the targets of top-level definitions keep their name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxRules {
+    ellipsis: String,
+    literals: Vec<String>,
+    rules: Vec<(Expression, Expression)>,
+}
+
+/// What a pattern variable matched. Variables under an ellipsis match once per
+/// repetition.
+#[derive(Debug, Clone)]
+enum Binding {
+    One(Expression),
+    Many(Vec<Binding>),
+}
+
+type Bindings = HashMap<String, Binding>;
+
+/// The identifiers bound by the template around the part being instantiated,
+/// which are renamed for this expansion.
+#[derive(Debug, Clone)]
+struct Renames {
+    expansion: usize,
+    bound: HashSet<String>,
+}
+
+impl Renames {
+    fn bind(&mut self, name: Option<&str>) {
+        if let Some(name) = name {
+            self.bound.insert(name.to_string());
+        }
+    }
+    /// The alias contains a space, so the reader never produces it and it cannot
+    /// collide with an identifier of the user.
+    fn alias(&self, name: &str) -> Option<String> {
+        self.bound
+            .contains(name)
+            .then(|| format!("{name} {}", self.expansion))
+    }
+}
+
+/// How the identifiers of a template are instantiated: renamed where the
+/// template binds them in code, kept in quoted data.
+#[derive(Debug, Clone, Copy)]
+enum Context<'a> {
+    Code(&'a Renames),
+    Quoted,
+    /// Quasiquoted data, where the unquoted parts are code again.
+    Quasiquoted(&'a Renames),
+}
+
+impl SyntaxRules {
+    pub fn new(
+        ellipsis: String,
+        literals: Vec<String>,
+        rules: Vec<(Expression, Expression)>,
+    ) -> Self {
+        SyntaxRules {
+            ellipsis,
+            literals,
+            rules,
+        }
+    }
+    /// Rewrites the use `(keyword args...)` with the first rule whose pattern matches.
+    pub fn expand(
+        &self,
+        keyword: &str,
+        args: &[Expression],
+    ) -> Result<Expression, LispComputerError> {
+        for (pattern, template) in &self.rules {
+            // the keyword position of the pattern is ignored
+            let Expression::List(pattern, span) = pattern else {
+                continue;
+            };
+            let pattern = match pattern.as_slice() {
+                // `(_ . rest)` matches the arguments as a whole
+                [_, Expression::Variable(dot, _), rest] if dot == "." => rest.clone(),
+                [_, rest @ ..] => Expression::List(rest.to_vec(), span.clone()),
+                [] => continue,
+            };
+            let form = Expression::List(args.to_vec(), span.clone());
+            let mut bindings = Bindings::new();
+            if !self.matches(&pattern, &form, &mut bindings)? {
+                continue;
+            }
+            let renames = Renames {
+                expansion: EXPANSIONS.with(|expansions| expansions.replace(expansions.get() + 1)),
+                bound: HashSet::new(),
+            };
+            return self.instantiate(template, &bindings, Context::Code(&renames));
+        }
+        Err(LispComputerError::InvalidArguments(
+            keyword.to_string(),
+            args.to_vec(),
+        ))
+    }
+
+    fn is_ellipsis(&self, expression: &Expression) -> bool {
+        matches!(expression, Expression::Variable(name, _) if *name == self.ellipsis)
+    }
+
+    fn matches(
+        &self,
+        pattern: &Expression,
+        form: &Expression,
+        bindings: &mut Bindings,
+    ) -> Result<bool, LispComputerError> {
+        match pattern {
+            Expression::Variable(name, _) if name == "_" => Ok(true),
+            Expression::Variable(name, _) if self.literals.contains(name) => {
+                Ok(matches!(form, Expression::Variable(form, _) if form == name))
+            }
+            Expression::Variable(name, _) => {
+                bindings.insert(name.to_string(), Binding::One(form.clone()));
+                Ok(true)
+            }
+            Expression::List(patterns, _) => {
+                let Expression::List(forms, span) = form else {
+                    return Ok(false);
+                };
+                let (patterns, tail) = split_dotted_list(patterns)?;
+                let (before, repeated, after) =
+                    match patterns.iter().position(|p| self.is_ellipsis(p)) {
+                        Some(0) | None => (patterns, None, &[][..]),
+                        Some(index) => (
+                            &patterns[..index - 1],
+                            Some(&patterns[index - 1]),
+                            &patterns[index + 1..],
+                        ),
+                    };
+                let fixed = before.len() + after.len();
+                if forms.len() < fixed
+                    || (repeated.is_none() && tail.is_none() && forms.len() != fixed)
+                {
+                    return Ok(false);
+                }
+                for (pattern, form) in before.iter().zip(forms) {
+                    if !self.matches(pattern, form, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                let rest = &forms[before.len()..];
+                let (middle, rest) = match repeated {
+                    Some(_) => rest.split_at(rest.len() - after.len()),
+                    None => rest.split_at(0),
+                };
+                if let Some(repeated) = repeated {
+                    let mut matches = Vec::new();
+                    for form in middle {
+                        let mut inner = Bindings::new();
+                        if !self.matches(repeated, form, &mut inner)? {
+                            return Ok(false);
+                        }
+                        matches.push(inner);
+                    }
+                    for name in self.pattern_variables(repeated) {
+                        let items = matches
+                            .iter_mut()
+                            .filter_map(|inner| inner.remove(&name))
+                            .collect();
+                        bindings.insert(name, Binding::Many(items));
+                    }
+                }
+                for (pattern, form) in after.iter().zip(rest) {
+                    if !self.matches(pattern, form, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                match tail {
+                    Some(tail) => {
+                        let rest = Expression::List(rest[after.len()..].to_vec(), span.clone());
+                        self.matches(tail, &rest, bindings)
+                    }
+                    None => Ok(true),
+                }
+            }
+            // numbers and strings match themselves
+            pattern => Ok(pattern == form),
+        }
+    }
+
+    fn pattern_variables(&self, pattern: &Expression) -> Vec<String> {
+        match pattern {
+            Expression::Variable(name, _)
+                if name != "_"
+                    && name != "."
+                    && *name != self.ellipsis
+                    && !self.literals.contains(name) =>
+            {
+                vec![name.to_string()]
+            }
+            Expression::List(patterns, _) => patterns
+                .iter()
+                .flat_map(|pattern| self.pattern_variables(pattern))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the identifier `expression` binds when it is one the template
+    /// introduces itself.
+    fn binder<'a>(&self, expression: &'a Expression, bindings: &Bindings) -> Option<&'a str> {
+        match expression {
+            Expression::Variable(name, _)
+                if !bindings.contains_key(name)
+                    && name != "."
+                    && name != "_"
+                    && *name != self.ellipsis =>
+            {
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    /// Extends `renames` with the template identifiers bound by `formals`, where
+    /// optional parameters are `(name default)`, and by the defines of `body`.
+    fn scope(
+        &self,
+        renames: &Renames,
+        bindings: &Bindings,
+        formals: &[&Expression],
+        body: &[Expression],
+    ) -> Renames {
+        let mut renames = renames.clone();
+        for formals in formals {
+            match formals {
+                Expression::List(params, _) => {
+                    for param in params {
+                        let param = match param {
+                            Expression::List(optional, _) => optional.first(),
+                            name => Some(name),
+                        };
+                        renames.bind(param.and_then(|param| self.binder(param, bindings)));
+                    }
+                }
+                name => renames.bind(self.binder(name, bindings)),
+            }
+        }
+        for expression in body {
+            let Expression::List(items, _) = expression else {
+                continue;
+            };
+            match items.as_slice() {
+                [
+                    Expression::Variable(define, _),
+                    Expression::List(signature, _),
+                    ..,
+                ] if define == "define" => renames.bind(
+                    signature
+                        .first()
+                        .and_then(|name| self.binder(name, bindings)),
+                ),
+                [Expression::Variable(define, _), name, ..] if define == "define" => {
+                    renames.bind(self.binder(name, bindings))
+                }
+                _ => {}
+            }
+        }
+        renames
+    }
+
+    fn instantiate(
+        &self,
+        template: &Expression,
+        bindings: &Bindings,
+        context: Context,
+    ) -> Result<Expression, LispComputerError> {
+        match template {
+            Expression::Variable(name, span) => match bindings.get(name) {
+                Some(Binding::One(expression)) => Ok(expression.clone()),
+                // a repeated variable must be followed by an ellipsis
+                Some(Binding::Many(_)) => Err(LispComputerError::InvalidArguments(
+                    self.ellipsis.to_string(),
+                    vec![template.clone()],
+                )),
+                None => {
+                    let alias = match context {
+                        Context::Code(renames) => renames.alias(name),
+                        Context::Quoted | Context::Quasiquoted(_) => None,
+                    };
+                    Ok(Expression::Variable(
+                        alias.unwrap_or_else(|| name.to_string()),
+                        span.clone(),
+                    ))
+                }
+            },
+            Expression::List(items, span) => {
+                // `(... ...)` escapes the ellipsis
+                if let [ellipsis, escaped] = items.as_slice()
+                    && self.is_ellipsis(ellipsis)
+                {
+                    return Ok(escaped.clone());
+                }
+                let expressions = match self.instantiate_form(items, bindings, context)? {
+                    Some(expressions) => expressions,
+                    None => self.instantiate_items(items, bindings, &self.with(context))?,
+                };
+                Ok(Expression::List(
+                    splice_dotted_tail(expressions),
+                    span.clone(),
+                ))
+            }
+            template => Ok(template.clone()),
+        }
+    }
+
+    /// Returns a function instantiating templates in `context`.
+    fn with<'a>(
+        &'a self,
+        context: Context<'a>,
+    ) -> impl Fn(&Expression, &Bindings) -> Result<Expression, LispComputerError> + 'a {
+        move |template, bindings| self.instantiate(template, bindings, context)
+    }
+
+    /// Instantiates the items of a list template with `instantiate`, repeating
+    /// the items followed by ellipses.
+    fn instantiate_items(
+        &self,
+        items: &[Expression],
+        bindings: &Bindings,
+        instantiate: &dyn Fn(&Expression, &Bindings) -> Result<Expression, LispComputerError>,
+    ) -> Result<Vec<Expression>, LispComputerError> {
+        let mut expressions = Vec::new();
+        let mut index = 0;
+        while index < items.len() {
+            let depth = items[index + 1..]
+                .iter()
+                .take_while(|item| self.is_ellipsis(item))
+                .count();
+            self.repeat(
+                &items[index],
+                depth,
+                bindings,
+                instantiate,
+                &mut expressions,
+            )?;
+            index += depth + 1;
+        }
+        Ok(expressions)
+    }
+
+    /// Instantiates the quoting and binding forms, which change how the
+    /// identifiers inside them are renamed. Other lists give `None`.
+    fn instantiate_form(
+        &self,
+        items: &[Expression],
+        bindings: &Bindings,
+        context: Context,
+    ) -> Result<Option<Vec<Expression>>, LispComputerError> {
+        let [Expression::Variable(head, _), rest @ ..] = items else {
+            return Ok(None);
+        };
+        if bindings.contains_key(head) {
+            return Ok(None);
+        }
+        let renames = match context {
+            // a keyword the template rebinds is an ordinary call
+            Context::Code(renames) if renames.alias(head).is_none() => renames,
+            Context::Quasiquoted(renames) if head == "unquote" || head == "unquote-splicing" => {
+                let code = self.with(Context::Code(renames));
+                return self.instantiate_items(items, bindings, &code).map(Some);
+            }
+            _ => return Ok(None),
+        };
+        // the binding parts come first, an ellipsis after them repeats a whole part
+        let fixed = match head.as_str() {
+            "lambda" | "define" | "let-values" | "let*" | "letrec" | "letrec*" | "do" | "guard" => {
+                1
+            }
+            "let" if matches!(rest.first(), Some(Expression::Variable(..))) => 2,
+            "let" => 1,
+            _ => 0,
+        };
+        if rest.len() < fixed || rest.get(fixed).is_some_and(|item| self.is_ellipsis(item)) {
+            return Ok(None);
+        }
+        let mut expressions = vec![items[0].clone()];
+        match (head.as_str(), rest) {
+            ("quote", _) => {
+                let quoted = self.with(Context::Quoted);
+                expressions.extend(self.instantiate_items(rest, bindings, &quoted)?)
+            }
+            ("quasiquote", _) => {
+                let quasiquoted = self.with(Context::Quasiquoted(renames));
+                expressions.extend(self.instantiate_items(rest, bindings, &quasiquoted)?)
+            }
+            ("lambda", [formals, body @ ..]) => {
+                let inner = self.scope(renames, bindings, &[formals], body);
+                expressions.extend(self.instantiate_items(
+                    rest,
+                    bindings,
+                    &self.with(Context::Code(&inner)),
+                )?)
+            }
+            ("case-lambda", clauses) => {
+                let clause = |clause: &Expression, bindings: &Bindings| match clause {
+                    Expression::List(items, span) if !items.is_empty() => {
+                        let inner = self.scope(renames, bindings, &[&items[0]], &items[1..]);
+                        let items = self.instantiate_items(
+                            items,
+                            bindings,
+                            &self.with(Context::Code(&inner)),
+                        )?;
+                        Ok(Expression::List(splice_dotted_tail(items), span.clone()))
+                    }
+                    clause => self.instantiate(clause, bindings, Context::Code(renames)),
+                };
+                expressions.extend(self.instantiate_items(clauses, bindings, &clause)?)
+            }
+            // the name is bound by the enclosing body, at the top level it is not
+            // renamed and the definition is seen by the user
+            ("define", [Expression::List(signature, span), body @ ..]) if !signature.is_empty() => {
+                let formals = Expression::List(signature[1..].to_vec(), span.clone());
+                let inner = self.scope(renames, bindings, &[&formals], body);
+                let code = self.with(Context::Code(&inner));
+                let mut signature_items =
+                    vec![self.instantiate(&signature[0], bindings, Context::Code(renames))?];
+                signature_items.extend(self.instantiate_items(&signature[1..], bindings, &code)?);
+                expressions.push(Expression::List(
+                    splice_dotted_tail(signature_items),
+                    span.clone(),
+                ));
+                expressions.extend(self.instantiate_items(body, bindings, &code)?)
+            }
+            ("let" | "let-values" | "let*" | "letrec" | "letrec*" | "do", _) => {
+                let (name, entries, span, body) = match rest {
+                    [name, Expression::List(entries, span), body @ ..] if fixed == 2 => {
+                        (Some(name), entries, span, body)
+                    }
+                    [Expression::List(entries, span), body @ ..] => (None, entries, span, body),
+                    _ => return Ok(None),
+                };
+                let mut formals: Vec<&Expression> = name.into_iter().collect();
+                for entry in entries {
+                    if let Expression::List(entry, _) = entry
+                        && let Some(variables) = entry.first()
+                    {
+                        formals.push(variables);
+                    }
+                }
+                let inner = if head == "do" {
+                    self.scope(renames, bindings, &formals, &[])
+                } else {
+                    self.scope(renames, bindings, &formals, body)
+                };
+                if let Some(name) = name {
+                    expressions.push(self.instantiate(name, bindings, Context::Code(&inner))?);
+                }
+                // the inits of `let`, `let-values` and `do` are outside of the scope,
+                // each one of `let*` sees the variables before it
+                let seen = RefCell::new(renames.clone());
+                let entry = |entry: &Expression, bindings: &Bindings| match entry {
+                    Expression::List(items, span) if items.len() >= 2 => {
+                        let inits = match head.as_str() {
+                            "letrec" | "letrec*" => inner.clone(),
+                            _ => seen.borrow().clone(),
+                        };
+                        if head == "let*" {
+                            let mut seen = seen.borrow_mut();
+                            *seen = self.scope(&seen, bindings, &[&items[0]], &[]);
+                        }
+                        let mut expressions =
+                            vec![self.instantiate(&items[0], bindings, Context::Code(&inner))?];
+                        expressions.push(self.instantiate(
+                            &items[1],
+                            bindings,
+                            Context::Code(&inits),
+                        )?);
+                        expressions.extend(self.instantiate_items(
+                            &items[2..],
+                            bindings,
+                            &self.with(Context::Code(&inner)),
+                        )?);
+                        Ok(Expression::List(expressions, span.clone()))
+                    }
+                    entry => self.instantiate(entry, bindings, Context::Code(&inner)),
+                };
+                let entries = self.instantiate_items(entries, bindings, &entry)?;
+                expressions.push(Expression::List(entries, span.clone()));
+                expressions.extend(self.instantiate_items(
+                    body,
+                    bindings,
+                    &self.with(Context::Code(&inner)),
+                )?)
+            }
+            // the variable is bound in the clauses, the body has its own scope
+            ("guard", [Expression::List(spec, span), body @ ..]) if !spec.is_empty() => {
+                let inner = self.scope(renames, bindings, &[&spec[0]], &[]);
+                let spec =
+                    self.instantiate_items(spec, bindings, &self.with(Context::Code(&inner)))?;
+                expressions.push(Expression::List(spec, span.clone()));
+                let inner = self.scope(renames, bindings, &[], body);
+                expressions.extend(self.instantiate_items(
+                    body,
+                    bindings,
+                    &self.with(Context::Code(&inner)),
+                )?)
+            }
+            _ => return Ok(None),
+        }
+        Ok(Some(expressions))
+    }
+
+    /// Instantiates a template followed by `depth` ellipses, once per repetition
+    /// of the pattern variables under them.
+    fn repeat(
+        &self,
+        template: &Expression,
+        depth: usize,
+        bindings: &Bindings,
+        instantiate: &dyn Fn(&Expression, &Bindings) -> Result<Expression, LispComputerError>,
+        expressions: &mut Vec<Expression>,
+    ) -> Result<(), LispComputerError> {
+        if depth == 0 {
+            expressions.push(instantiate(template, bindings)?);
+            return Ok(());
+        }
+        let repeated: Vec<(String, &Vec<Binding>)> = self
+            .pattern_variables(template)
+            .into_iter()
+            .filter_map(|name| match bindings.get(&name) {
+                Some(Binding::Many(items)) => Some((name, items)),
+                _ => None,
+            })
+            .collect();
+        let Some(count) = repeated.first().map(|(_, items)| items.len()) else {
+            return Err(LispComputerError::InvalidArguments(
+                self.ellipsis.to_string(),
+                vec![template.clone()],
+            ));
+        };
+        if repeated.iter().any(|(_, items)| items.len() != count) {
+            return Err(LispComputerError::InvalidArguments(
+                self.ellipsis.to_string(),
+                vec![template.clone()],
+            ));
+        }
+        for index in 0..count {
+            let mut inner = bindings.clone();
+            for (name, items) in &repeated {
+                inner.insert(name.to_string(), items[index].clone());
+            }
+            self.repeat(template, depth - 1, &inner, instantiate, expressions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splices a dotted tail that became a list, as in `(f . args)`, into the list.
+fn splice_dotted_tail(mut expressions: Vec<Expression>) -> Vec<Expression> {
+    if let [.., Expression::Variable(dot, _), Expression::List(..)] = expressions.as_slice()
+        && dot == "."
+        && let Some(Expression::List(tail, _)) = expressions.pop()
+    {
+        expressions.pop();
+        expressions.extend(tail);
+    }
+    expressions
+}
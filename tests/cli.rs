@@ -0,0 +1,98 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn lisp(args: &[&str], stdin: &str) -> anyhow::Result<Output> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_lisp"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("no stdin"))?
+        .write_all(stdin.as_bytes())?;
+    Ok(child.wait_with_output()?)
+}
+
+fn script(name: &str, source: &str) -> anyhow::Result<PathBuf> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, source)?;
+    Ok(path)
+}
+
+#[test]
+fn script_file_test() -> anyhow::Result<()> {
+    let path = script(
+        "args.lisp",
+        "(display (car *args*))\n(display (length *args*))\n",
+    )?;
+    let output = lisp(&[path.to_str().unwrap_or_default(), "first", "second"], "")?;
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout)?, "first2");
+    Ok(())
+}
+
+#[test]
+fn expression_test() -> anyhow::Result<()> {
+    let output = lisp(&["-e", "(list (+ 1 2) *args*)", "a"], "")?;
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout)?, "(3 (\"a\"))\n");
+    Ok(())
+}
+
+#[test]
+fn stdin_test() -> anyhow::Result<()> {
+    let output = lisp(
+        &["-", "x"],
+        "(define n 4)\n(display (* n n))\n(display *args*)",
+    )?;
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout)?, "16(\"x\")");
+    Ok(())
+}
+
+#[test]
+fn runtime_error_test() -> anyhow::Result<()> {
+    let output = lisp(&["-"], "(display 1)\n(car 1)\n(display 2)")?;
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout)?, "1");
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.starts_with("Error: <stdin>:2:1: Operation car mismatch"),
+        "{stderr}"
+    );
+    Ok(())
+}
+
+#[test]
+fn parse_error_test() -> anyhow::Result<()> {
+    let path = script("unclosed.lisp", "(define x 1)\n(f x\n")?;
+    let output = lisp(&[path.to_str().unwrap_or_default()], "")?;
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr)?;
+    assert_eq!(
+        stderr,
+        format!(
+            "Error: {}:2:1: invalid input, expected `)` to close the list\n",
+            path.display()
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn usage_test() -> anyhow::Result<()> {
+    for args in [&["-x"][..], &["-e"]] {
+        let output = lisp(args, "")?;
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(String::from_utf8(output.stderr)?.starts_with("usage: lisp"));
+    }
+    let output = lisp(&["missing-script.lisp"], "")?;
+    assert_eq!(output.status.code(), Some(1));
+    Ok(())
+}